@@ -1,4 +1,6 @@
 use std::env;
+use std::path::Path;
+use std::process::Command;
 
 fn main() {
     // Solo compilar recursos en Windows
@@ -7,4 +9,20 @@ fn main() {
         res.set_icon("pdf_procu.ico");
         res.compile().expect("Error compilando recursos de Windows");
     }
+
+    // Commit del que se compiló, para --version; sin git (un paquete de fuentes) se omite
+    let hash = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|salida| salida.status.success())
+        .and_then(|salida| String::from_utf8(salida.stdout).ok());
+    if let Some(hash) = hash {
+        println!("cargo:rustc-env=GIT_HASH={}", hash.trim());
+    }
+    for archivo in [".git/HEAD", ".git/index"] {
+        if Path::new(archivo).exists() {
+            println!("cargo:rerun-if-changed={}", archivo);
+        }
+    }
 }
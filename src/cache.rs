@@ -0,0 +1,115 @@
+//! Caché en disco del texto extraído de cada página.
+//!
+//! Extraer el texto es la parte lenta del procesamiento; al ajustar los
+//! patrones y volver a correr sobre el mismo PDF, el texto guardado se pasa
+//! directamente a los extractores. Cada PDF tiene su archivo de caché,
+//! identificado por la ruta, la fecha de modificación y el tamaño: si el PDF
+//! cambia, el caché anterior deja de coincidir y se vuelve a extraer.
+//!
+//! Se guarda el texto tal como sale del PDF, antes de limpiarlo, para que
+//! cambiar las opciones de limpieza no deje el caché desactualizado.
+
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+/// Identificación del PDF y de la forma de extraer el texto.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+struct Clave {
+    ruta: PathBuf,
+    /// Fecha de modificación, en nanosegundos desde 1970
+    modificado: u128,
+    tamaño: u64,
+    /// Columnas en que se leyó el texto (ver `Config::columnas_texto`)
+    columnas: usize,
+}
+
+/// Contenido del archivo de caché.
+#[derive(Debug, Serialize, Deserialize)]
+struct Contenido {
+    clave: Clave,
+    paginas: BTreeMap<u32, String>,
+}
+
+/// Texto de las páginas de un PDF, compartido entre los hilos que lo procesan.
+pub(crate) struct CacheTexto {
+    archivo: PathBuf,
+    clave: Clave,
+    paginas: Mutex<BTreeMap<u32, String>>,
+    /// Si se agregaron páginas que todavía no se guardaron
+    modificado: AtomicBool,
+}
+
+impl CacheTexto {
+    /// Abre el caché del PDF `pdf` dentro de `directorio`; vacío si no existe o
+    /// si el PDF cambió. `None` si no se pudo leer la fecha o el tamaño del PDF.
+    pub(crate) fn abrir(directorio: &Path, pdf: &Path, columnas: usize) -> Option<CacheTexto> {
+        let metadatos = std::fs::metadata(pdf).ok()?;
+        let modificado = metadatos.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+        let clave = Clave {
+            ruta: std::fs::canonicalize(pdf).unwrap_or_else(|_| pdf.to_path_buf()),
+            modificado: modificado.as_nanos(),
+            tamaño: metadatos.len(),
+            columnas,
+        };
+        let mut hasher = DefaultHasher::new();
+        clave.hash(&mut hasher);
+        let archivo = directorio.join(format!("{:016x}.json", hasher.finish()));
+
+        let paginas = std::fs::read(&archivo)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Contenido>(&bytes).ok())
+            .filter(|contenido| contenido.clave == clave)
+            .map(|contenido| contenido.paginas)
+            .unwrap_or_default();
+        Some(CacheTexto {
+            archivo,
+            clave,
+            paginas: Mutex::new(paginas),
+            modificado: AtomicBool::new(false),
+        })
+    }
+
+    /// Texto guardado de la página, o el que devuelve `extraer`, que se agrega al caché.
+    pub(crate) fn texto(
+        &self,
+        page_num: u32,
+        extraer: impl FnOnce() -> lopdf::Result<String>,
+    ) -> lopdf::Result<String> {
+        if let Some(texto) = self.paginas.lock().unwrap().get(&page_num) {
+            return Ok(texto.clone());
+        }
+        let texto = extraer()?;
+        self.paginas.lock().unwrap().insert(page_num, texto.clone());
+        self.modificado.store(true, Ordering::Relaxed);
+        Ok(texto)
+    }
+
+    /// Escribe el caché si se agregaron páginas.
+    pub(crate) fn guardar(&self) -> std::io::Result<()> {
+        if !self.modificado.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let paginas = self.paginas.lock().unwrap();
+        let contenido = ContenidoRef {
+            clave: &self.clave,
+            paginas: &paginas,
+        };
+        if let Some(directorio) = self.archivo.parent() {
+            std::fs::create_dir_all(directorio)?;
+        }
+        std::fs::write(&self.archivo, serde_json::to_vec(&contenido)?)
+    }
+}
+
+/// [`Contenido`] prestado, para escribirlo sin copiar el texto.
+#[derive(Serialize)]
+struct ContenidoRef<'a> {
+    clave: &'a Clave,
+    paginas: &'a BTreeMap<u32, String>,
+}
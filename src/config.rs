@@ -0,0 +1,363 @@
+//! Parámetros de configuración de la extracción.
+
+use crate::extraccion::{patron_nombre, PATRON_AUTOS};
+use crate::ProcError;
+use chrono::Datelike;
+use regex::Regex;
+use serde::Deserialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Configuración de los extractores.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Primer año aceptado al separar el año del expediente
+    pub año_min: i32,
+    /// Último año aceptado al separar el año del expediente
+    pub año_max: i32,
+    /// Contraseña para abrir PDF encriptados
+    pub contraseña: Option<String>,
+    /// Probar un patrón de expediente más permisivo cuando fallan los demás
+    pub expediente_relajado: bool,
+    /// Cantidad mínima de caracteres para que una página se procese
+    pub min_caracteres: usize,
+    /// Omitir las páginas sin monto "($...)" ni expediente, como carátulas e índices largos
+    pub omitir_sin_datos: bool,
+    /// Columnas de igual ancho en que se divide la página para leer el texto por
+    /// posición; con 1 se usa el orden del flujo de contenido del PDF
+    pub columnas_texto: usize,
+    /// Patrones de expediente adicionales, probados después de los incorporados
+    pub patrones_expediente: Vec<Regex>,
+    /// Patrones del nombre entre comillas, uno por palabra clave ("autos",
+    /// "caratulados"...), probados en orden
+    pub patrones_nombre: Vec<Regex>,
+    /// Dígitos del número de cheque; los números más largos se recortan a esta longitud
+    pub longitud_cheque: usize,
+    /// Reglas que eligen el prefijo del número INTERNO; se aplica la primera que coincide
+    pub reglas_prefijo: Vec<ReglaPrefijo>,
+    /// Prefijo del número INTERNO cuando ninguna regla coincide
+    pub prefijo_interno: String,
+    /// Dejar vacíos los campos no encontrados en lugar de usar el número de página
+    pub vacio_si_falta: bool,
+    /// Páginas a procesar (numeradas desde 1); `None` procesa todas
+    pub paginas: Option<SeleccionPaginas>,
+    /// Cantidad mínima de páginas del documento; con menos se rechaza
+    pub min_paginas: usize,
+    /// Cantidad máxima de páginas del documento; con más se rechaza. `None` no tiene límite
+    pub max_paginas: Option<usize>,
+    /// Dejar de procesar el documento cuando esta cantidad de páginas produjo
+    /// registros, para probar los patrones en un formato nuevo. `None` procesa todo
+    pub muestra: Option<usize>,
+    /// Reconocer con OCR las páginas que no tienen texto suficiente
+    pub ocr: bool,
+    /// Generar un registro por cada nombre entre comillas de la página, no solo el primero
+    pub todas_las_caratulas: bool,
+    /// Generar un registro por cada cheque de la página, no solo el primero
+    pub todos_los_cheques: bool,
+    /// Anotar en las observaciones los números de cheque inverosímiles (cero o muy cortos)
+    pub validar_cheques: bool,
+    /// Qué monto entre paréntesis tomar cuando la página tiene varios
+    pub estrategia_monto: EstrategiaMonto,
+    /// Decimales a los que se redondea el monto extraído (a par en los empates).
+    /// `None` deja el monto como se extrajo
+    pub decimales_monto: Option<usize>,
+    /// Quitar los acentos del texto antes de extraer ("é" → "e", "ñ" → "n")
+    pub plegar_acentos: bool,
+    /// Textos entre comillas que no son un nombre y se descartan (sin distinguir mayúsculas)
+    pub frases_descartadas: Vec<String>,
+    /// Directorio donde guardar el texto limpio de cada página (`page_<n>.txt`),
+    /// para depurar los patrones
+    pub volcado_texto: Option<PathBuf>,
+    /// Directorio del caché del texto extraído de cada página, para no volver a
+    /// extraerlo de un PDF que no cambió. `None` no usa caché
+    pub cache_texto: Option<PathBuf>,
+    /// Finales que se quitan del nombre, como "y otros" (sin distinguir mayúsculas)
+    pub sufijos_nombre: Vec<String>,
+    /// Largo máximo del nombre, en caracteres; los más largos se recortan con "…"
+    /// y el nombre completo queda en las observaciones. `None` no recorta
+    pub largo_max_nombre: Option<usize>,
+    /// Extractores propios que agregan campos a cada registro; ver [`Config::agregar_extractor`]
+    pub extractores: Extractores,
+}
+
+/// Extractor propio de un campo adicional: recibe el texto limpio de la página y
+/// devuelve el nombre del campo, que es el encabezado de su columna, y su valor.
+pub type ExtractorCampo = Arc<dyn Fn(&str) -> (String, String) + Send + Sync>;
+
+/// Lista de extractores propios, en el orden en que se agregan sus columnas.
+#[derive(Clone, Default)]
+pub struct Extractores(pub Vec<ExtractorCampo>);
+
+impl fmt::Debug for Extractores {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Extractores({})", self.0.len())
+    }
+}
+
+/// Monto que se toma cuando la página tiene varios montos "($...)" distintos.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EstrategiaMonto {
+    /// El primero que aparece en el texto
+    #[default]
+    Primero,
+    /// El de mayor importe; útil cuando los demás son montos citados de otras causas
+    Mayor,
+    /// El último que aparece en el texto
+    Ultimo,
+}
+
+impl EstrategiaMonto {
+    /// Interpreta el nombre de la estrategia: "primero", "mayor" o "ultimo".
+    pub fn desde_nombre(nombre: &str) -> Option<EstrategiaMonto> {
+        match nombre.to_lowercase().as_str() {
+            "primero" | "first" => Some(EstrategiaMonto::Primero),
+            "mayor" | "largest" => Some(EstrategiaMonto::Mayor),
+            "ultimo" | "último" | "last" => Some(EstrategiaMonto::Ultimo),
+            _ => None,
+        }
+    }
+
+    /// Nombre de la estrategia, para las observaciones.
+    pub fn nombre(self) -> &'static str {
+        match self {
+            EstrategiaMonto::Primero => "el primero",
+            EstrategiaMonto::Mayor => "el mayor",
+            EstrategiaMonto::Ultimo => "el último",
+        }
+    }
+
+    /// Elige uno de los montos ya normalizados, en el orden en que aparecen.
+    ///
+    /// Con [`EstrategiaMonto::Mayor`] los montos que no son numéricos se ignoran.
+    pub fn elegir(self, montos: &[String]) -> Option<&String> {
+        match self {
+            EstrategiaMonto::Primero => montos.first(),
+            EstrategiaMonto::Ultimo => montos.last(),
+            EstrategiaMonto::Mayor => montos
+                .iter()
+                .filter_map(|monto| monto.parse::<f64>().ok().map(|valor| (valor, monto)))
+                .max_by(|a, b| a.0.total_cmp(&b.0))
+                .map(|(_, monto)| monto),
+        }
+    }
+}
+
+/// Asigna `prefijo` al número INTERNO de las páginas que contienen `palabra_clave`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReglaPrefijo {
+    pub palabra_clave: String,
+    pub prefijo: String,
+}
+
+impl ReglaPrefijo {
+    pub fn new(palabra_clave: &str, prefijo: &str) -> Self {
+        ReglaPrefijo {
+            palabra_clave: palabra_clave.to_string(),
+            prefijo: prefijo.to_string(),
+        }
+    }
+}
+
+impl Default for Config {
+    /// Acepta años desde 1990 hasta el año actual del sistema.
+    fn default() -> Self {
+        Config {
+            año_min: 1990,
+            año_max: chrono::Local::now().year(),
+            contraseña: None,
+            expediente_relajado: false,
+            min_caracteres: 500,
+            omitir_sin_datos: false,
+            columnas_texto: 1,
+            patrones_expediente: Vec::new(),
+            patrones_nombre: vec![Regex::new(PATRON_AUTOS).unwrap()],
+            longitud_cheque: 8,
+            reglas_prefijo: vec![ReglaPrefijo::new("M.E.P.", "MEP")],
+            prefijo_interno: "ITB".to_string(),
+            vacio_si_falta: false,
+            paginas: None,
+            min_paginas: 0,
+            max_paginas: None,
+            muestra: None,
+            ocr: false,
+            todas_las_caratulas: false,
+            todos_los_cheques: false,
+            validar_cheques: false,
+            estrategia_monto: EstrategiaMonto::default(),
+            decimales_monto: None,
+            plegar_acentos: false,
+            volcado_texto: None,
+            cache_texto: None,
+            frases_descartadas: vec!["ut-supra".to_string(), "ut -supra".to_string()],
+            sufijos_nombre: Vec::new(),
+            largo_max_nombre: None,
+            extractores: Extractores::default(),
+        }
+    }
+}
+
+/// Páginas elegidas, numeradas desde 1, como rangos que incluyen ambos extremos.
+///
+/// Los rangos se guardan ordenados y sin superponerse, sin enumerar sus páginas:
+/// "1-4294967295" ocupa lo mismo que "1-3".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeleccionPaginas(Vec<(u32, u32)>);
+
+impl SeleccionPaginas {
+    /// Si la página está en alguno de los rangos.
+    pub fn contiene(&self, pagina: u32) -> bool {
+        self.0
+            .iter()
+            .any(|&(desde, hasta)| (desde..=hasta).contains(&pagina))
+    }
+
+    /// Partes de la selección que pasan de las `total` páginas del documento,
+    /// como "12" o "12-20".
+    pub fn fuera_de(&self, total: u32) -> Vec<String> {
+        self.0
+            .iter()
+            .filter(|&&(_, hasta)| hasta > total)
+            .map(|&(desde, hasta)| match desde.max(total.saturating_add(1)) {
+                desde if desde == hasta => desde.to_string(),
+                desde => format!("{}-{}", desde, hasta),
+            })
+            .collect()
+    }
+}
+
+/// Interpreta una selección de páginas como "10-25", "10,12,30" o "1-3,7".
+///
+/// Las páginas se numeran desde 1 y los rangos incluyen ambos extremos.
+pub fn parsear_paginas(rango: &str) -> Result<SeleccionPaginas, ProcError> {
+    let error = |mensaje: String| ProcError::RangoPaginas {
+        rango: rango.to_string(),
+        mensaje,
+    };
+    let numero = |texto: &str| -> Result<u32, ProcError> {
+        match texto.trim().parse::<u32>() {
+            Ok(0) => Err(error("las páginas se numeran desde 1".to_string())),
+            Ok(n) => Ok(n),
+            Err(_) => Err(error(format!(
+                "{:?} no es un número de página",
+                texto.trim()
+            ))),
+        }
+    };
+
+    let mut rangos = Vec::new();
+    for parte in rango.split(',') {
+        match parte.split_once('-') {
+            Some((desde, hasta)) => {
+                let (desde, hasta) = (numero(desde)?, numero(hasta)?);
+                if desde > hasta {
+                    return Err(error(format!(
+                        "el rango {}-{} está invertido",
+                        desde, hasta
+                    )));
+                }
+                rangos.push((desde, hasta));
+            }
+            None => {
+                let pagina = numero(parte)?;
+                rangos.push((pagina, pagina));
+            }
+        }
+    }
+
+    // Unir los rangos que se superponen o son contiguos
+    rangos.sort_unstable();
+    let mut unidos: Vec<(u32, u32)> = Vec::with_capacity(rangos.len());
+    for (desde, hasta) in rangos {
+        match unidos.last_mut() {
+            Some(ultimo) if desde <= ultimo.1.saturating_add(1) => ultimo.1 = ultimo.1.max(hasta),
+            _ => unidos.push((desde, hasta)),
+        }
+    }
+    Ok(SeleccionPaginas(unidos))
+}
+
+/// Contenido del archivo de patrones externos.
+#[derive(Debug, Deserialize)]
+struct ArchivoPatrones {
+    #[serde(default)]
+    patrones_expediente: Vec<String>,
+    #[serde(default)]
+    claves_nombre: Vec<String>,
+    #[serde(default)]
+    reglas_prefijo: Vec<ReglaPrefijo>,
+    #[serde(default)]
+    frases_descartadas: Vec<String>,
+    #[serde(default)]
+    sufijos_nombre: Vec<String>,
+}
+
+impl Config {
+    /// Agrega un extractor propio que se ejecuta en cada página después de los
+    /// incorporados; el campo que devuelve va a una columna adicional de la salida.
+    ///
+    /// ```
+    /// # use pdf_procuracion::Config;
+    /// let mut config = Config::default();
+    /// config.agregar_extractor(|texto| {
+    ///     let provincia = if texto.contains("Córdoba") { "CBA" } else { "" };
+    ///     ("Provincia".to_string(), provincia.to_string())
+    /// });
+    /// ```
+    pub fn agregar_extractor<F>(&mut self, extractor: F)
+    where
+        F: Fn(&str) -> (String, String) + Send + Sync + 'static,
+    {
+        self.extractores.0.push(Arc::new(extractor));
+    }
+
+    /// Agrega los patrones de expediente y las reglas de prefijo de un archivo TOML o JSON.
+    ///
+    /// El archivo define una lista `patrones_expediente` y una lista
+    /// `reglas_prefijo` de `{ palabra_clave, prefijo }`; ambas se prueban en el
+    /// orden dado, después de las incorporadas. Las listas opcionales
+    /// `claves_nombre`, `frases_descartadas` y `sufijos_nombre` se suman a las
+    /// de extracción y limpieza del nombre. Se interpreta como JSON si la
+    /// extensión es `.json` y como TOML en cualquier otro caso.
+    pub fn cargar_patrones(&mut self, path: &Path) -> Result<(), ProcError> {
+        let error = |mensaje: String| ProcError::ArchivoPatrones {
+            path: path.to_path_buf(),
+            mensaje,
+        };
+
+        let contenido = std::fs::read_to_string(path).map_err(|e| error(e.to_string()))?;
+        let es_json = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        let archivo: ArchivoPatrones = if es_json {
+            serde_json::from_str(&contenido).map_err(|e| error(e.to_string()))?
+        } else {
+            toml::from_str(&contenido).map_err(|e| error(e.to_string()))?
+        };
+
+        for patron in archivo.patrones_expediente {
+            let regex = Regex::new(&patron)
+                .map_err(|source| ProcError::PatronInvalido { patron, source })?;
+            self.patrones_expediente.push(regex);
+        }
+        for clave in &archivo.claves_nombre {
+            self.agregar_clave_nombre(clave)?;
+        }
+        self.reglas_prefijo.extend(archivo.reglas_prefijo);
+        self.frases_descartadas.extend(archivo.frases_descartadas);
+        self.sufijos_nombre.extend(archivo.sufijos_nombre);
+
+        Ok(())
+    }
+
+    /// Agrega una palabra clave que precede al nombre entre comillas.
+    ///
+    /// Se prueba después de las ya configuradas; la palabra se toma literal.
+    pub fn agregar_clave_nombre(&mut self, clave: &str) -> Result<(), ProcError> {
+        let patron = patron_nombre(clave);
+        let regex =
+            Regex::new(&patron).map_err(|source| ProcError::PatronInvalido { patron, source })?;
+        self.patrones_nombre.push(regex);
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+//! Errores del procesamiento de PDF y de la escritura de resultados.
+
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Error devuelto por las funciones públicas de la biblioteca.
+///
+/// Cada variante indica la etapa que falló y la ruta o página involucrada.
+#[derive(Debug, Error)]
+pub enum ProcError {
+    #[error("No se pudo abrir el PDF {path:?}: {source}")]
+    PdfLoad {
+        path: PathBuf,
+        #[source]
+        source: lopdf::Error,
+    },
+
+    #[error("No se pudo leer la imagen {path:?}: {mensaje}")]
+    Imagen { path: PathBuf, mensaje: String },
+
+    #[error("PDF está protegido con contraseña: {path:?}")]
+    PdfProtegido { path: PathBuf },
+
+    #[error("La contraseña del PDF es incorrecta: {path:?}")]
+    ContraseñaIncorrecta { path: PathBuf },
+
+    #[error(
+        "El PDF {path:?} tiene {paginas} páginas pero casi no tiene texto; \
+         probablemente es un escaneo y necesita OCR"
+    )]
+    SinTexto { path: PathBuf, paginas: usize },
+
+    #[error("Rango de páginas inválido {rango:?}: {mensaje}")]
+    RangoPaginas { rango: String, mensaje: String },
+
+    #[error("El PDF {path:?} tiene {total} páginas; no existen las páginas {paginas}")]
+    PaginasInexistentes {
+        path: PathBuf,
+        total: usize,
+        paginas: String,
+    },
+
+    #[error(
+        "El PDF {path:?} tiene {paginas} páginas, fuera del rango esperado ({rango}); \
+         ¿es el archivo correcto?"
+    )]
+    CantidadPaginas {
+        path: PathBuf,
+        paginas: usize,
+        rango: String,
+    },
+
+    #[error("No se pudo leer el archivo de patrones {path:?}: {mensaje}")]
+    ArchivoPatrones { path: PathBuf, mensaje: String },
+
+    #[error("No se pudo leer el archivo de montos esperados {path:?}: {mensaje}")]
+    MontosEsperados { path: PathBuf, mensaje: String },
+
+    #[error("Patrón inválido {patron:?}: {source}")]
+    PatronInvalido {
+        patron: String,
+        #[source]
+        source: regex::Error,
+    },
+
+    #[error("No se pudo leer el directorio {path:?}: {source}")]
+    Directorio {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("No se puede escribir {path:?}: {mensaje}")]
+    ExtensionNoSoportada { path: PathBuf, mensaje: String },
+
+    #[error("No se pudo leer el Excel existente {path:?}: {source}")]
+    ExcelRead {
+        path: PathBuf,
+        #[source]
+        source: calamine::XlsxError,
+    },
+
+    #[error("No se pudo leer la planilla ODS existente {path:?}: {source}")]
+    OdsRead {
+        path: PathBuf,
+        #[source]
+        source: calamine::OdsError,
+    },
+
+    #[error("No se pudo escribir el Excel {path:?}: {source}")]
+    ExcelWrite {
+        path: PathBuf,
+        #[source]
+        source: rust_xlsxwriter::XlsxError,
+    },
+
+    #[error("No se pudo escribir el archivo {path:?}: {source}")]
+    Exportar {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+}
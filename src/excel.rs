@@ -0,0 +1,1538 @@
+//! Escritura del archivo Excel con las hojas REND y PDF.
+//!
+//! El libro se genera siempre desde cero: `rust_xlsxwriter` no puede abrir un
+//! archivo existente, así que no es posible rellenar una plantilla con logos y
+//! estilos propios sin perderlos. Para conservar una plantilla institucional, la
+//! plantilla debe leer los datos del archivo generado: las tablas tienen nombres
+//! fijos ([`TABLA_PDF`] y [`TABLA_REND`]) que se pueden consultar desde la
+//! plantilla con Power Query ("Obtener datos > Desde un libro") y actualizar
+//! después de cada procesamiento.
+
+use crate::extraccion::{MONEDA_DOLARES, MONEDA_PESOS};
+use crate::{
+    detectar_duplicados, nombres_campos_extra, Confianza, ConfianzaCampos, DatosPagina, ProcError,
+};
+use calamine::{open_workbook, Data, DataType, Ods, Reader, Xlsx};
+use rust_xlsxwriter::utility::column_number_to_name;
+use rust_xlsxwriter::{
+    Color, DocProperties, Format, Formula, Table, TableColumn, TableFunction, TableStyle, Workbook,
+    Worksheet, XlsxError,
+};
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Seek};
+use std::path::{Path, PathBuf};
+
+/// Nombre de la hoja de rendición que se escribe y, por defecto, se lee
+pub const HOJA_REND: &str = "REND";
+
+/// Nombre de la tabla de la hoja PDF, para consultarla desde otros libros
+pub const TABLA_PDF: &str = "TablaPDF";
+
+/// Nombre de la tabla de la hoja REND, para consultarla desde otros libros
+pub const TABLA_REND: &str = "TablaREND";
+
+/// Opciones de escritura del archivo Excel
+#[derive(Debug, Clone, Default)]
+pub struct OpcionesExcel {
+    /// Conservar las filas existentes de la hoja PDF y agregar las nuevas debajo
+    pub anexar: bool,
+    /// Hoja del archivo existente de la que se leen los datos de rendición;
+    /// `None` equivale a [`HOJA_REND`]
+    pub hoja_origen: Option<String>,
+    /// Estilo de las tablas de ambas hojas, por nombre ("Medium9"); `None` usa Light1.
+    /// Ver [`estilo_tabla`]
+    pub estilo_tabla: Option<String>,
+    /// No escribir la hoja REND ni las fórmulas de la hoja PDF que la consultan
+    pub sin_rend: bool,
+    /// Idioma de los encabezados
+    pub idioma: Idioma,
+    /// Orden de las columnas de la hoja PDF; las que no se nombran siguen en el
+    /// orden habitual. `None` usa el orden habitual. Ver [`orden_columnas`]
+    pub orden_columnas: Option<Vec<ColumnaPdf>>,
+    /// Ruta del PDF o directorio de origen, que se guarda en las propiedades del documento
+    pub origen: Option<String>,
+    /// Al anexar, agregar también los registros que ya están en la hoja PDF
+    /// (mismo cheque, monto y expediente); por defecto se saltean
+    pub sin_deduplicar: bool,
+    /// Cruzar las filas de REND que no coinciden exactamente con la hoja PDF
+    /// comparando el cheque o el expediente normalizados (ver [`clave_aproximada`])
+    pub cruce_aproximado: bool,
+    /// Filas libres sobre los encabezados de la hoja PDF, para un título o
+    /// membrete de la plantilla; los encabezados van en la fila `fila_inicial + 1`.
+    /// El contenido de esas filas en el archivo existente se conserva
+    pub fila_inicial: u32,
+}
+
+/// Columnas de la hoja PDF, en su orden habitual.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnaPdf {
+    Nombre,
+    Expediente,
+    Año,
+    Monto,
+    MontoOriginal,
+    Cheque,
+    Control,
+    ControlCheque,
+    Archivo,
+    Observaciones,
+    Moneda,
+    Juzgado,
+    Fecha,
+    Pagina,
+}
+
+impl ColumnaPdf {
+    /// Todas las columnas, en el orden habitual de la hoja PDF
+    pub const TODAS: [ColumnaPdf; 14] = [
+        ColumnaPdf::Nombre,
+        ColumnaPdf::Expediente,
+        ColumnaPdf::Año,
+        ColumnaPdf::Monto,
+        ColumnaPdf::MontoOriginal,
+        ColumnaPdf::Cheque,
+        ColumnaPdf::Control,
+        ColumnaPdf::ControlCheque,
+        ColumnaPdf::Archivo,
+        ColumnaPdf::Observaciones,
+        ColumnaPdf::Moneda,
+        ColumnaPdf::Juzgado,
+        ColumnaPdf::Fecha,
+        ColumnaPdf::Pagina,
+    ];
+
+    /// Busca la columna por el nombre del campo, sin distinguir mayúsculas:
+    /// "nombre", "expediente", "anio", "monto", "control_cheque", "pagina"...
+    pub fn desde_nombre(nombre: &str) -> Option<ColumnaPdf> {
+        let columna = match nombre.trim().to_lowercase().as_str() {
+            "nombre" => ColumnaPdf::Nombre,
+            "expediente" => ColumnaPdf::Expediente,
+            "año" | "anio" => ColumnaPdf::Año,
+            "monto" => ColumnaPdf::Monto,
+            "monto_original" | "monto-original" => ColumnaPdf::MontoOriginal,
+            "cheque" => ColumnaPdf::Cheque,
+            "control" => ColumnaPdf::Control,
+            "control_cheque" | "control-cheque" => ColumnaPdf::ControlCheque,
+            "archivo" => ColumnaPdf::Archivo,
+            "observaciones" => ColumnaPdf::Observaciones,
+            "moneda" => ColumnaPdf::Moneda,
+            "juzgado" => ColumnaPdf::Juzgado,
+            "fecha" => ColumnaPdf::Fecha,
+            "pagina" | "página" => ColumnaPdf::Pagina,
+            _ => return None,
+        };
+        Some(columna)
+    }
+}
+
+/// Orden completo de las columnas de la hoja PDF: primero las pedidas, sin
+/// repetir, y después las restantes en el orden habitual.
+pub fn orden_columnas(pedidas: &[ColumnaPdf]) -> Vec<ColumnaPdf> {
+    let mut orden: Vec<ColumnaPdf> = Vec::with_capacity(ColumnaPdf::TODAS.len());
+    for &columna in pedidas.iter().chain(&ColumnaPdf::TODAS) {
+        if !orden.contains(&columna) {
+            orden.push(columna);
+        }
+    }
+    orden
+}
+
+/// Ubicación de cada columna en la hoja PDF según el orden elegido, y de la
+/// fila de encabezados según `OpcionesExcel::fila_inicial`.
+pub(crate) struct Disposicion {
+    /// Columna de la hoja en que va cada [`ColumnaPdf`], indexado por su orden habitual
+    posiciones: [u16; 14],
+    /// Fila de la hoja (desde 0) con los encabezados
+    fila_encabezados: u32,
+}
+
+impl Disposicion {
+    fn new(orden: &[ColumnaPdf], fila_encabezados: u32) -> Self {
+        let mut posiciones = [0; 14];
+        for (i, &columna) in orden.iter().enumerate() {
+            posiciones[columna as usize] = i as u16;
+        }
+        Disposicion {
+            posiciones,
+            fila_encabezados,
+        }
+    }
+
+    pub(crate) fn desde_opciones(opciones: &OpcionesExcel) -> Self {
+        Disposicion::new(
+            &orden_columnas(opciones.orden_columnas.as_deref().unwrap_or_default()),
+            opciones.fila_inicial,
+        )
+    }
+
+    /// Fila de la hoja (desde 0) con los encabezados
+    pub(crate) fn fila_encabezados(&self) -> u32 {
+        self.fila_encabezados
+    }
+
+    /// Fila de la hoja (desde 0) del registro `indice`
+    pub(crate) fn fila(&self, indice: usize) -> u32 {
+        self.fila_encabezados + 1 + indice as u32
+    }
+
+    /// Número de fila (desde 1) del registro `indice`, como se escribe en las fórmulas
+    pub(crate) fn numero_fila(&self, indice: usize) -> u32 {
+        self.fila(indice) + 1
+    }
+
+    /// Índice de la columna en la hoja
+    pub(crate) fn col(&self, columna: ColumnaPdf) -> u16 {
+        self.posiciones[columna as usize]
+    }
+
+    /// Letra de la columna en la hoja, para las fórmulas ("D")
+    pub(crate) fn letra(&self, columna: ColumnaPdf) -> String {
+        column_number_to_name(self.col(columna))
+    }
+
+    /// Reordena valores dados en el orden habitual al orden de la hoja.
+    pub(crate) fn ordenar<T: Clone>(&self, valores: &[T]) -> Vec<T> {
+        let mut ordenados = valores.to_vec();
+        for (i, valor) in valores.iter().enumerate() {
+            ordenados[self.posiciones[i] as usize] = valor.clone();
+        }
+        ordenados
+    }
+}
+
+/// Estilos de tabla de Excel que se pueden elegir por nombre.
+const ESTILOS_TABLA: [TableStyle; 61] = [
+    TableStyle::None,
+    TableStyle::Light1,
+    TableStyle::Light2,
+    TableStyle::Light3,
+    TableStyle::Light4,
+    TableStyle::Light5,
+    TableStyle::Light6,
+    TableStyle::Light7,
+    TableStyle::Light8,
+    TableStyle::Light9,
+    TableStyle::Light10,
+    TableStyle::Light11,
+    TableStyle::Light12,
+    TableStyle::Light13,
+    TableStyle::Light14,
+    TableStyle::Light15,
+    TableStyle::Light16,
+    TableStyle::Light17,
+    TableStyle::Light18,
+    TableStyle::Light19,
+    TableStyle::Light20,
+    TableStyle::Light21,
+    TableStyle::Medium1,
+    TableStyle::Medium2,
+    TableStyle::Medium3,
+    TableStyle::Medium4,
+    TableStyle::Medium5,
+    TableStyle::Medium6,
+    TableStyle::Medium7,
+    TableStyle::Medium8,
+    TableStyle::Medium9,
+    TableStyle::Medium10,
+    TableStyle::Medium11,
+    TableStyle::Medium12,
+    TableStyle::Medium13,
+    TableStyle::Medium14,
+    TableStyle::Medium15,
+    TableStyle::Medium16,
+    TableStyle::Medium17,
+    TableStyle::Medium18,
+    TableStyle::Medium19,
+    TableStyle::Medium20,
+    TableStyle::Medium21,
+    TableStyle::Medium22,
+    TableStyle::Medium23,
+    TableStyle::Medium24,
+    TableStyle::Medium25,
+    TableStyle::Medium26,
+    TableStyle::Medium27,
+    TableStyle::Medium28,
+    TableStyle::Dark1,
+    TableStyle::Dark2,
+    TableStyle::Dark3,
+    TableStyle::Dark4,
+    TableStyle::Dark5,
+    TableStyle::Dark6,
+    TableStyle::Dark7,
+    TableStyle::Dark8,
+    TableStyle::Dark9,
+    TableStyle::Dark10,
+    TableStyle::Dark11,
+];
+
+/// Busca el estilo de tabla de Excel con ese nombre, sin distinguir mayúsculas:
+/// "None", "Light1" a "Light21", "Medium1" a "Medium28" o "Dark1" a "Dark11".
+pub fn estilo_tabla(nombre: &str) -> Option<TableStyle> {
+    ESTILOS_TABLA.into_iter().find(|estilo| {
+        let completo = estilo.to_string();
+        completo
+            .strip_prefix("TableStyle")
+            .unwrap_or(&completo)
+            .eq_ignore_ascii_case(nombre)
+    })
+}
+
+/// Comprueba que la ruta de salida tenga extensión `.xlsx`, el único formato de
+/// Excel que se puede escribir.
+///
+/// Conviene llamarla antes de procesar el PDF para no descubrir el problema al final.
+pub fn validar_salida_excel(path: &Path) -> Result<(), ProcError> {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let mensaje = match extension.as_str() {
+        "xlsx" => return Ok(()),
+        "xls" => "el formato .xls de Excel 97-2003 no está soportado; \
+                  use .xlsx, que también abren las versiones 2007 en adelante"
+            .to_string(),
+        "" => "falta la extensión .xlsx".to_string(),
+        otra => format!("la extensión .{} no es de Excel; use .xlsx", otra),
+    };
+    Err(ProcError::ExtensionNoSoportada {
+        path: path.to_path_buf(),
+        mensaje,
+    })
+}
+
+/// Agrega la extensión `.xlsx` a una ruta de salida que no tiene ninguna, como
+/// la que se escribe sin extensión en el diálogo de guardado, y rechaza con
+/// [`validar_salida_excel`] las extensiones que no son de Excel.
+pub fn normalizar_salida_excel(path: &Path) -> Result<PathBuf, ProcError> {
+    if path.extension().is_none() {
+        return Ok(path.with_extension("xlsx"));
+    }
+    validar_salida_excel(path)?;
+    Ok(path.to_path_buf())
+}
+
+/// Convierte una fila de la hoja PDF existente en `DatosPagina`.
+///
+/// La confianza no se guarda en la planilla: un campo vacío o igual al número
+/// de página, o un monto sin moneda, se toma como valor de respaldo. Se asume
+/// que la hoja se escribió con la misma disposición de columnas. Las columnas
+/// posteriores a las habituales son campos adicionales, con los nombres de `extra`.
+fn fila_a_datos(fila: &[Data], disposicion: &Disposicion, extra: &[String]) -> DatosPagina {
+    let valor = |columna: ColumnaPdf| fila.get(disposicion.col(columna) as usize);
+    let texto = |columna: ColumnaPdf| valor(columna).map(|c| c.to_string()).unwrap_or_default();
+    let pagina = valor(ColumnaPdf::Pagina)
+        .and_then(|c| c.as_f64())
+        .unwrap_or(0.0) as usize;
+    let confianza = |valor: &str| {
+        if valor.trim().is_empty() || (pagina > 0 && valor.trim() == pagina.to_string()) {
+            Confianza::Respaldo
+        } else {
+            Confianza::Encontrado
+        }
+    };
+    let confianza = ConfianzaCampos {
+        nombre: confianza(&texto(ColumnaPdf::Nombre)),
+        expediente: confianza(&texto(ColumnaPdf::Expediente)),
+        año: if texto(ColumnaPdf::Año).trim().is_empty() {
+            Confianza::Respaldo
+        } else {
+            Confianza::Encontrado
+        },
+        monto: if texto(ColumnaPdf::Moneda).is_empty() {
+            Confianza::Respaldo
+        } else {
+            Confianza::Encontrado
+        },
+        cheque: confianza(&texto(ColumnaPdf::Cheque)),
+    };
+    DatosPagina {
+        nombre: texto(ColumnaPdf::Nombre),
+        expediente: texto(ColumnaPdf::Expediente),
+        año: texto(ColumnaPdf::Año),
+        monto: texto(ColumnaPdf::Monto),
+        monto_original: texto(ColumnaPdf::MontoOriginal),
+        cheque: texto(ColumnaPdf::Cheque),
+        moneda: texto(ColumnaPdf::Moneda),
+        juzgado: texto(ColumnaPdf::Juzgado),
+        fecha: valor(ColumnaPdf::Fecha).and_then(|c| c.as_date()),
+        archivo: texto(ColumnaPdf::Archivo),
+        pagina,
+        observaciones: texto(ColumnaPdf::Observaciones)
+            .split("; ")
+            .filter(|o| !o.is_empty())
+            .map(str::to_string)
+            .collect(),
+        confianza,
+        patron_expediente: None,
+        campos_extra: extra
+            .iter()
+            .zip(fila.iter().skip(ColumnaPdf::TODAS.len()))
+            .map(|(nombre, celda)| (nombre.clone(), celda.to_string()))
+            .filter(|(_, valor)| !valor.is_empty())
+            .collect(),
+    }
+}
+
+/// Idioma de los encabezados de las hojas.
+///
+/// Solo cambian los encabezados: los nombres de las hojas (REND y PDF) se
+/// mantienen porque las fórmulas de control se refieren a ellos.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Idioma {
+    #[default]
+    Español,
+    Inglés,
+}
+
+impl Idioma {
+    /// Interpreta el código o el nombre del idioma: "es", "en", "español", "english"...
+    pub fn desde_nombre(nombre: &str) -> Option<Idioma> {
+        match nombre.to_lowercase().as_str() {
+            "es" | "español" | "espanol" | "spanish" => Some(Idioma::Español),
+            "en" | "inglés" | "ingles" | "english" => Some(Idioma::Inglés),
+            _ => None,
+        }
+    }
+
+    /// Columnas propias de la hoja REND; las que siguen son agregadas a mano y se conservan
+    pub(crate) fn columnas_rend(self) -> [&'static str; 8] {
+        match self {
+            Idioma::Español => [
+                "Número de cheque",
+                "Monto",
+                "Autos",
+                "Expediente",
+                "Año",
+                "Observaciones",
+                "Control",
+                "Control cheque",
+            ],
+            Idioma::Inglés => [
+                "Check number",
+                "Amount",
+                "Case title",
+                "Case",
+                "Year",
+                "Notes",
+                "Control",
+                "Check control",
+            ],
+        }
+    }
+
+    /// Columnas de la hoja PDF, en el orden en que se escriben.
+    ///
+    /// Todos los encabezados, de ambas hojas, llevan mayúscula solo en la primera
+    /// palabra; las tablas usan exactamente estos textos.
+    pub(crate) fn columnas_pdf(self) -> [&'static str; 14] {
+        match self {
+            Idioma::Español => [
+                "Nombre",
+                "Expediente",
+                "Año",
+                "Monto",
+                "Monto original",
+                "Cheque",
+                "Control",
+                "Control cheque",
+                "Archivo",
+                "Observaciones",
+                "Moneda",
+                "Juzgado",
+                "Fecha",
+                "Página",
+            ],
+            Idioma::Inglés => [
+                "Name",
+                "Case",
+                "Year",
+                "Amount",
+                "Original amount",
+                "Check",
+                "Control",
+                "Check control",
+                "File",
+                "Notes",
+                "Currency",
+                "Court",
+                "Date",
+                "Page",
+            ],
+        }
+    }
+}
+
+/// Etiqueta de la fila de totales de la tabla REND
+pub(crate) const ETIQUETA_TOTAL: &str = "Total";
+
+/// Datos existentes del archivo de salida que hay que conservar.
+#[derive(Debug, Default)]
+pub(crate) struct DatosExistentes {
+    /// Fila de encabezados de la hoja de rendición
+    encabezados_rend: Vec<String>,
+    /// Filas de la hoja de rendición, sin los encabezados
+    filas_rend: Vec<Vec<String>>,
+    /// Registros previos de la hoja PDF (solo al anexar)
+    datos_pdf: Vec<DatosPagina>,
+    /// Celdas de la hoja PDF sobre los encabezados (ver `OpcionesExcel::fila_inicial`)
+    membrete_pdf: Vec<CeldaMembrete>,
+    /// Mensajes para el usuario sobre lo que se leyó, como la hoja usada en lugar
+    /// de la pedida
+    mensajes: Vec<String>,
+}
+
+/// Celda de las filas sobre los encabezados de la hoja PDF: fila, columna y texto.
+pub(crate) type CeldaMembrete = (u32, u16, String);
+
+/// Lee las filas de la hoja REND y, si se anexa, las de la hoja PDF.
+fn leer_existente(
+    output_path: &Path,
+    opciones: &OpcionesExcel,
+) -> Result<DatosExistentes, calamine::XlsxError> {
+    let mut workbook: Xlsx<_> = open_workbook(output_path)?;
+    leer_hojas(&mut workbook, opciones)
+}
+
+/// Igual que [`leer_existente`], pero de una planilla ODS.
+pub(crate) fn leer_existente_ods(
+    path: &Path,
+    opciones: &OpcionesExcel,
+) -> Result<DatosExistentes, calamine::OdsError> {
+    let mut workbook: Ods<_> = open_workbook(path)?;
+    leer_hojas(&mut workbook, opciones)
+}
+
+/// Lee las hojas REND y PDF de un libro ya abierto, en cualquier formato.
+fn leer_hojas<RS, R>(
+    workbook: &mut R,
+    opciones: &OpcionesExcel,
+) -> Result<DatosExistentes, R::Error>
+where
+    RS: Read + Seek,
+    R: Reader<RS>,
+{
+    let mut datos_previos: Vec<DatosPagina> = Vec::new();
+    let mut membrete_pdf: Vec<CeldaMembrete> = Vec::new();
+    let mut mensajes = Vec::new();
+    let disposicion = Disposicion::desde_opciones(opciones);
+
+    if let Ok(range) = workbook.worksheet_range("PDF") {
+        // Las posiciones de `range` se cuentan desde su primera celda con datos
+        let (inicio, columna_inicio) = range.start().unwrap_or_default();
+        let encabezados = disposicion.fila_encabezados();
+
+        // Fila de encabezados del archivo existente, reconocida por el de la
+        // columna Nombre; puede no coincidir si se escribió con otra fila inicial
+        let col_nombre = disposicion.col(ColumnaPdf::Nombre) as usize;
+        let nombres = [Idioma::Español, Idioma::Inglés]
+            .map(|idioma| idioma.columnas_pdf()[ColumnaPdf::Nombre as usize]);
+        let previa = range
+            .rows()
+            .position(|fila| {
+                fila.get(col_nombre)
+                    .is_some_and(|celda| nombres.contains(&celda.to_string().as_str()))
+            })
+            .map(|i| inicio + i as u32)
+            .unwrap_or(encabezados);
+        if previa != encabezados {
+            mensajes.push(format!(
+                "La hoja PDF existente tiene los encabezados en la fila {}; no se conservan las filas de arriba",
+                previa + 1
+            ));
+        }
+
+        // El título o membrete de la plantilla, si los encabezados siguen en su fila
+        membrete_pdf = range
+            .cells()
+            .map(|(fila, col, celda)| {
+                let fila = inicio + fila as u32;
+                (
+                    fila,
+                    (columna_inicio as usize + col) as u16,
+                    celda.to_string(),
+                )
+            })
+            .filter(|(fila, _, texto)| {
+                previa == encabezados && *fila < encabezados && !texto.is_empty()
+            })
+            .collect();
+
+        // En modo anexar, conservar también las filas de la hoja PDF
+        if opciones.anexar {
+            let mut filas = range
+                .rows()
+                .enumerate()
+                .filter(|(i, _)| inicio + *i as u32 >= previa)
+                .map(|(_, fila)| fila);
+            let extra: Vec<String> = filas
+                .next()
+                .unwrap_or_default()
+                .iter()
+                .skip(ColumnaPdf::TODAS.len())
+                .map(|celda| celda.to_string())
+                .collect();
+            datos_previos = filas
+                .filter(|row| row.iter().any(|cell| *cell != Data::Empty))
+                .map(|fila| fila_a_datos(fila, &disposicion, &extra))
+                .collect();
+        }
+    }
+
+    // Sin hoja REND no hay datos de rendición que conservar
+    if opciones.sin_rend {
+        if workbook.sheet_names().iter().any(|n| n == HOJA_REND) {
+            mensajes.push(format!(
+                "Se descarta la hoja {} del archivo existente",
+                HOJA_REND
+            ));
+        }
+        return Ok(DatosExistentes {
+            datos_pdf: datos_previos,
+            membrete_pdf,
+            mensajes,
+            ..DatosExistentes::default()
+        });
+    }
+
+    let hoja = opciones.hoja_origen.as_deref().unwrap_or(HOJA_REND);
+    let range = match workbook.worksheet_range(hoja) {
+        Ok(range) => Some(range),
+        Err(_) => {
+            let range = workbook.worksheet_range_at(0).and_then(Result::ok);
+            if range.is_some() {
+                let usada = workbook.sheet_names().first().cloned().unwrap_or_default();
+                mensajes.push(format!(
+                    "No se encontró la hoja {:?}; se leen los datos existentes de la hoja {:?}",
+                    hoja, usada
+                ));
+            }
+            range
+        }
+    };
+
+    let mut filas = range
+        .map(|range| {
+            range
+                .rows()
+                .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                .collect::<Vec<Vec<String>>>()
+        })
+        .unwrap_or_default()
+        .into_iter();
+    let encabezados_rend = filas.next().unwrap_or_default();
+
+    // La fila de totales se vuelve a generar al escribir
+    let mut filas_rend: Vec<Vec<String>> = filas.collect();
+    if filas_rend
+        .last()
+        .is_some_and(|fila| fila.first().is_some_and(|c| c == ETIQUETA_TOTAL))
+    {
+        filas_rend.pop();
+    }
+
+    Ok(DatosExistentes {
+        encabezados_rend,
+        filas_rend,
+        datos_pdf: datos_previos,
+        membrete_pdf,
+        mensajes,
+    })
+}
+
+/// Clave con la que se reconoce un registro ya anexado: cheque, monto y expediente.
+///
+/// El monto se compara como número, porque al leerlo de la planilla pierde los
+/// ceros decimales ("2000.50" vuelve como "2000.5").
+fn clave_registro(dato: &DatosPagina) -> (String, String, String) {
+    let monto = match dato.monto.parse::<f64>() {
+        Ok(monto) => format!("{:.2}", monto),
+        Err(_) => dato.monto.clone(),
+    };
+    (dato.cheque.clone(), monto, dato.expediente.clone())
+}
+
+/// Registros y filas de rendición que se escriben en la planilla.
+pub(crate) struct Planilla {
+    /// Registros de la hoja PDF: los existentes, si se anexa, y los nuevos
+    pub(crate) datos: Vec<DatosPagina>,
+    pub(crate) encabezados_rend: Vec<String>,
+    pub(crate) filas_rend: Vec<Vec<String>>,
+    /// Registro de la hoja PDF con el que se cruzó cada fila de REND por
+    /// aproximación; vacío sin `opciones.cruce_aproximado`. Ver [`cruce_aproximado`]
+    pub(crate) cruces: Vec<Option<usize>>,
+    /// Celdas sobre los encabezados de la hoja PDF del archivo existente
+    pub(crate) membrete_pdf: Vec<CeldaMembrete>,
+    /// Mensajes para el usuario sobre lo que se hizo con los datos existentes
+    pub(crate) mensajes: Vec<String>,
+}
+
+/// Etiqueta del aviso de REND para los cheques repetidos en la hoja PDF
+const AVISO_DUPLICADO: &str = "cheque duplicado en hoja PDF";
+
+/// Etiqueta del aviso de REND con la fila de la hoja PDF con la que se cruzó
+/// por aproximación
+const AVISO_CRUCE: &str = "cruce aproximado";
+
+/// Cheque o expediente reducido para compararlo sin importar cómo se escribió:
+/// en mayúsculas, sin espacios ni signos, sin el prefijo ("CH", "EXP", "Nro.")
+/// ni los ceros a la izquierda.
+///
+/// "ch 0012345" y "CH 12345" dan "12345"; "Expte. 555/2021" y "EXP-555/2021", "5552021".
+pub(crate) fn clave_aproximada(texto: &str) -> String {
+    let clave: String = texto
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_uppercase)
+        .collect();
+    let clave = ["CHEQUE", "EXPTE", "EXP", "CH"]
+        .iter()
+        .find_map(|prefijo| clave.strip_prefix(prefijo))
+        .unwrap_or(&clave);
+    let clave = clave.strip_prefix("NRO").unwrap_or(clave);
+    clave.trim_start_matches('0').to_string()
+}
+
+/// Registro de la hoja PDF con el que se cruza cada fila de REND al comparar el
+/// cheque normalizado o, si no coincide, el expediente de la columna D.
+///
+/// Las filas que las fórmulas ya encuentran (mismo cheque o mismo monto) quedan
+/// en `None`, igual que las que no coinciden con ningún registro.
+pub(crate) fn cruce_aproximado(
+    datos: &[DatosPagina],
+    filas_rend: &[Vec<String>],
+) -> Vec<Option<usize>> {
+    filas_rend
+        .iter()
+        .map(|fila| {
+            let celda = |col: usize| fila.get(col).map(|v| v.trim()).unwrap_or("");
+            let cheque = celda(0).to_lowercase();
+            let monto = celda(1).replace(',', ".").parse::<f64>().ok();
+            let exacto = datos.iter().any(|d| {
+                (!cheque.is_empty() && d.cheque.to_lowercase() == cheque)
+                    || (monto.is_some() && d.monto.parse::<f64>().ok() == monto)
+            });
+            if exacto {
+                return None;
+            }
+            let por_cheque = Some(clave_aproximada(&cheque))
+                .filter(|clave| !clave.is_empty())
+                .and_then(|clave| {
+                    datos
+                        .iter()
+                        .position(|d| clave_aproximada(&d.cheque) == clave)
+                });
+            por_cheque.or_else(|| {
+                let clave = clave_aproximada(celda(3));
+                if clave.is_empty() {
+                    return None;
+                }
+                datos
+                    .iter()
+                    .position(|d| clave_aproximada(&d.expediente) == clave)
+            })
+        })
+        .collect()
+}
+
+/// Monto de la hoja REND como número, con coma o punto decimal.
+pub(crate) fn monto_rend(valor: &str) -> Option<f64> {
+    valor.replace(',', ".").parse::<f64>().ok()
+}
+
+/// Resultados de las fórmulas de una fila de REND. Se guardan junto con las
+/// fórmulas para que la planilla se vea bien sin recalcular y para que la próxima
+/// corrida lea el expediente (rust_xlsxwriter no calcula las fórmulas).
+pub(crate) struct ResultadosRend {
+    /// Expediente del registro encontrado, o el que ya tenía la fila si no hay
+    pub(crate) expediente: String,
+    /// El expediente que tenía la fila, si no se encontró ningún registro: la
+    /// fórmula lo muestra en lugar del error para no perderlo
+    pub(crate) expediente_previo: Option<String>,
+    pub(crate) año: Option<f64>,
+    /// Registros de la hoja PDF con el mismo monto (columna Control)
+    pub(crate) por_monto: usize,
+    /// Registros de la hoja PDF con el mismo cheque (columna Control cheque)
+    pub(crate) por_cheque: usize,
+}
+
+/// Calcula lo que dan las fórmulas de la fila de REND: el registro con el mismo
+/// cheque o, si no hay, el mismo monto, o el cruzado por aproximación (`cruce`).
+pub(crate) fn resultados_rend(
+    datos: &[DatosPagina],
+    fila: &[String],
+    cruce: Option<usize>,
+) -> ResultadosRend {
+    let cheque = fila.first().map(|c| c.to_lowercase()).unwrap_or_default();
+    let monto = fila.get(1).and_then(|m| monto_rend(m));
+    let mismo_monto = |d: &DatosPagina| monto.is_some() && d.monto.parse::<f64>().ok() == monto;
+    let registro = cruce.or_else(|| {
+        datos
+            .iter()
+            .position(|d| !cheque.is_empty() && d.cheque.to_lowercase() == cheque)
+            .or_else(|| datos.iter().position(mismo_monto))
+    });
+    // Un registro cruzado por aproximación se cuenta con su propio cheque
+    let cheque_contado = match cruce {
+        Some(i) => datos[i].cheque.to_lowercase(),
+        None => cheque,
+    };
+    // "0" o vacío: la fórmula de una corrida anterior sin resultado guardado
+    let expediente_previo = fila
+        .get(3)
+        .map(|e| e.trim().to_string())
+        .filter(|e| registro.is_none() && !clave_aproximada(e).is_empty());
+    ResultadosRend {
+        expediente: match registro {
+            Some(i) => datos[i].expediente.clone(),
+            None => expediente_previo.clone().unwrap_or_default(),
+        },
+        expediente_previo,
+        año: registro
+            .and_then(|i| datos[i].año.trim().parse::<f64>().ok())
+            .filter(|año| *año > 0.0),
+        por_monto: datos.iter().filter(|d| mismo_monto(d)).count(),
+        por_cheque: datos
+            .iter()
+            .filter(|d| !cheque_contado.is_empty() && d.cheque.to_lowercase() == cheque_contado)
+            .count(),
+    }
+}
+
+/// Observaciones de una fila de REND con los avisos del programa: el cheque
+/// duplicado en la hoja PDF y la fila (desde 1) con la que se cruzó por aproximación.
+///
+/// El aviso de cruce de una corrida anterior se reemplaza, porque la fila puede
+/// haber cambiado. `None` si las observaciones quedan como estaban.
+pub(crate) fn observaciones_rend(
+    fila: &[String],
+    duplicado: bool,
+    cruce: Option<usize>,
+) -> Option<String> {
+    let actual = fila.get(5).map(String::as_str).unwrap_or("");
+    let prefijo_cruce = crate::aviso(AVISO_CRUCE, "");
+    let mut partes: Vec<String> = actual
+        .split("; ")
+        .filter(|parte| !actual.is_empty() && !parte.starts_with(&prefijo_cruce))
+        .map(str::to_string)
+        .collect();
+    let aviso_duplicado = crate::aviso(AVISO_DUPLICADO, "");
+    if duplicado && !actual.contains(&aviso_duplicado) {
+        partes.push(aviso_duplicado);
+    }
+    if let Some(fila) = cruce {
+        let detalle = format!("fila {} de la hoja PDF", fila);
+        partes.push(crate::aviso(AVISO_CRUCE, &detalle));
+    }
+    let observaciones = partes.join("; ");
+    (observaciones != actual).then_some(observaciones)
+}
+
+/// Guarda los datos en un archivo Excel y aplica formato.
+///
+/// Devuelve los mensajes para el usuario, como los registros que se conservaron
+/// del archivo existente; no se muestran, para que decida quien llama.
+pub fn guardar_y_formatear_excel(
+    datos: &[DatosPagina],
+    output_path: &Path,
+    opciones: &OpcionesExcel,
+) -> Result<Vec<String>, ProcError> {
+    let output_path = &normalizar_salida_excel(output_path)?;
+
+    // Leer datos existentes de la hoja REND si existe
+    let existentes = if output_path.exists() {
+        leer_existente(output_path, opciones).map_err(|source| ProcError::ExcelRead {
+            path: output_path.to_path_buf(),
+            source,
+        })?
+    } else {
+        DatosExistentes::default()
+    };
+
+    let planilla = armar_planilla(existentes, datos, opciones);
+    escribir_excel(&planilla, output_path, opciones).map_err(|source| ProcError::ExcelWrite {
+        path: output_path.to_path_buf(),
+        source,
+    })?;
+    Ok(planilla.mensajes)
+}
+
+/// Une los datos del archivo existente con los nuevos registros.
+///
+/// Al anexar, los registros que ya estaban en la hoja PDF (ver [`clave_registro`])
+/// se saltean, salvo con `opciones.sin_deduplicar`. Con `opciones.cruce_aproximado`
+/// se cruzan también las filas de REND ([`cruce_aproximado`]).
+pub(crate) fn armar_planilla(
+    existentes: DatosExistentes,
+    datos: &[DatosPagina],
+    opciones: &OpcionesExcel,
+) -> Planilla {
+    let mut mensajes = existentes.mensajes;
+    if !existentes.datos_pdf.is_empty() {
+        mensajes.push(format!(
+            "Se conservan {} registros existentes de la hoja PDF",
+            existentes.datos_pdf.len()
+        ));
+    }
+
+    // Volver a procesar el mismo PDF no debe duplicar los registros ya anexados
+    let previos: HashSet<_> = existentes.datos_pdf.iter().map(clave_registro).collect();
+    let nuevos: Vec<&DatosPagina> = datos
+        .iter()
+        .filter(|dato| opciones.sin_deduplicar || !previos.contains(&clave_registro(dato)))
+        .collect();
+    if nuevos.len() < datos.len() {
+        mensajes.push(format!(
+            "Se omiten {} registros que ya estaban en la hoja PDF",
+            datos.len() - nuevos.len()
+        ));
+    }
+    let datos: Vec<DatosPagina> = existentes
+        .datos_pdf
+        .into_iter()
+        .chain(nuevos.into_iter().cloned())
+        .collect();
+
+    let encabezados_rend = encabezados_rend(
+        &existentes.encabezados_rend,
+        &existentes.filas_rend,
+        opciones.idioma,
+    );
+    let cruces = if opciones.cruce_aproximado && !opciones.sin_rend {
+        cruce_aproximado(&datos, &existentes.filas_rend)
+    } else {
+        Vec::new()
+    };
+    let cruzadas = cruces.iter().flatten().count();
+    if cruzadas > 0 {
+        mensajes.push(format!(
+            "Se cruzaron {} filas de REND por aproximación del cheque o el expediente",
+            cruzadas
+        ));
+    }
+    Planilla {
+        datos,
+        encabezados_rend,
+        filas_rend: existentes.filas_rend,
+        cruces,
+        membrete_pdf: existentes.membrete_pdf,
+        mensajes,
+    }
+}
+
+/// Arma los encabezados de la hoja REND: las columnas propias más las que el
+/// usuario haya agregado a la derecha en el archivo existente.
+///
+/// Las columnas agregadas sin encabezado se llaman "Columna N"; los nombres
+/// repetidos reciben el número de columna para que la tabla sea válida.
+fn encabezados_rend(previos: &[String], filas: &[Vec<String>], idioma: Idioma) -> Vec<String> {
+    let columnas = idioma.columnas_rend();
+    let ancho = filas
+        .iter()
+        .map(Vec::len)
+        .chain([previos.len(), columnas.len()])
+        .max()
+        .unwrap_or_default();
+
+    let mut encabezados: Vec<String> = columnas.iter().map(|h| h.to_string()).collect();
+    for col in columnas.len()..ancho {
+        let nombre = match previos.get(col).map(|h| h.trim()) {
+            Some(h) if !h.is_empty() => h.to_string(),
+            _ => format!("Columna {}", col + 1),
+        };
+        let nombre = if encabezados.contains(&nombre) {
+            format!("{} ({})", nombre, col + 1)
+        } else {
+            nombre
+        };
+        encabezados.push(nombre);
+    }
+    encabezados
+}
+
+/// Formato de número de los montos en pesos, con separadores argentinos ("$ 1.234.567,89")
+const FORMATO_PESOS: &str = "[$$-2C0A] #,##0.00";
+
+/// Formato de número de los montos en dólares ("U$S 1.500,00")
+const FORMATO_DOLARES: &str = "[$U$S-2C0A] #,##0.00";
+
+/// Símbolo con que se muestra la moneda en la planilla.
+pub(crate) fn simbolo_moneda(moneda: &str) -> &'static str {
+    if moneda == MONEDA_DOLARES {
+        "U$S"
+    } else {
+        "$"
+    }
+}
+
+/// Formato de celda para los montos de la moneda dada; pesos si no se indica.
+fn formato_monto(moneda: &str) -> Format {
+    if moneda == MONEDA_DOLARES {
+        Format::new().set_num_format(FORMATO_DOLARES)
+    } else {
+        Format::new().set_num_format(FORMATO_PESOS)
+    }
+}
+
+/// Texto del monto tal como lo muestra el formato de moneda, para medir anchos.
+pub(crate) fn monto_visible(monto: &str, moneda: &str) -> String {
+    let Ok(valor) = monto.parse::<f64>() else {
+        return monto.to_string();
+    };
+    let texto = format!("{:.2}", valor.abs());
+    let (entero, decimales) = texto.split_once('.').unwrap_or((&texto, "00"));
+    let mut miles = String::new();
+    for (i, c) in entero.chars().enumerate() {
+        if i > 0 && (entero.len() - i) % 3 == 0 {
+            miles.push('.');
+        }
+        miles.push(c);
+    }
+    let signo = if valor < 0.0 { "-" } else { "" };
+    format!(
+        "{}{} {},{}",
+        signo,
+        simbolo_moneda(moneda),
+        miles,
+        decimales
+    )
+}
+
+/// Resalta `formato` según la confianza del valor: fondo amarillo si es dudoso
+/// y letra roja en cursiva si es el número de página usado como respaldo.
+fn resaltar(formato: Format, confianza: Confianza) -> Format {
+    match confianza {
+        Confianza::Encontrado => formato,
+        Confianza::Ambiguo => formato.set_background_color(Color::Yellow),
+        Confianza::Respaldo => formato.set_font_color(Color::Red).set_italic(),
+    }
+}
+
+/// Ancho mínimo y máximo de las columnas, en caracteres
+const ANCHO_MIN: usize = 6;
+const ANCHO_MAX: usize = 60;
+
+/// Largo en caracteres del texto más largo de cada columna, encabezados incluidos.
+fn medir_columnas<'a>(
+    encabezados: impl IntoIterator<Item = &'a str>,
+    filas: impl IntoIterator<Item = Vec<String>>,
+) -> Vec<usize> {
+    let mut anchos: Vec<usize> = encabezados.into_iter().map(|h| h.chars().count()).collect();
+    for fila in filas {
+        for (col, celda) in fila.iter().enumerate() {
+            let largo = celda.chars().count();
+            match anchos.get_mut(col) {
+                Some(ancho) => *ancho = (*ancho).max(largo),
+                None => anchos.push(largo),
+            }
+        }
+    }
+    anchos
+}
+
+/// Fija el ancho de cada columna, con margen para el botón de filtro de la tabla.
+fn ajustar_anchos(worksheet: &mut Worksheet, anchos: &[usize]) -> Result<(), XlsxError> {
+    for (col, &ancho) in anchos.iter().enumerate() {
+        let ancho = ancho.clamp(ANCHO_MIN, ANCHO_MAX) + 3;
+        worksheet.set_column_width(col as u16, ancho as f64)?;
+    }
+    Ok(())
+}
+
+/// Textos que se muestran en cada columna de la hoja PDF, para medir anchos.
+fn textos_pdf(dato: &DatosPagina) -> Vec<String> {
+    vec![
+        dato.nombre.clone(),
+        dato.expediente.clone(),
+        dato.año.trim().to_string(),
+        monto_visible(&dato.monto, &dato.moneda),
+        dato.monto_original.clone(),
+        dato.cheque.clone(),
+        String::new(),
+        String::new(),
+        dato.archivo.clone(),
+        dato.observaciones.join("; "),
+        dato.moneda.clone(),
+        dato.juzgado.clone(),
+        dato.fecha
+            .map(|f| f.format("%d/%m/%Y").to_string())
+            .unwrap_or_default(),
+        dato.pagina.to_string(),
+    ]
+}
+
+/// Escribe la hoja REND con los datos de rendición, sus fórmulas de cruce con la
+/// hoja PDF y la tabla con la fila de totales.
+///
+/// Las filas cruzadas por aproximación (`cruces`) toman el expediente y el año
+/// directamente de la fila de la hoja PDF con la que se cruzaron.
+fn escribir_rend(
+    workbook: &mut Workbook,
+    datos: &[DatosPagina],
+    headers_rend: &[String],
+    datos_rend: &[Vec<String>],
+    cruces: &[Option<usize>],
+    estilo: TableStyle,
+    disposicion: &Disposicion,
+) -> Result<(), XlsxError> {
+    // Cheques repetidos en la hoja PDF, para marcarlos en REND
+    let duplicados: Vec<String> = detectar_duplicados(datos)
+        .into_iter()
+        .map(|d| d.cheque)
+        .collect();
+    let duplicados: HashSet<&str> = duplicados.iter().map(String::as_str).collect();
+
+    let formato_pesos = formato_monto(MONEDA_PESOS);
+
+    // Crear hoja REND
+    let worksheet_rend = workbook.add_worksheet();
+    worksheet_rend.set_name(HOJA_REND)?;
+
+    // Encabezados REND
+    for (col, header) in headers_rend.iter().enumerate() {
+        worksheet_rend.write_string(0, col as u16, header)?;
+    }
+
+    // Escribir datos REND existentes
+    let mut max_row_rend = 0u32;
+    for (row_idx, row_data) in datos_rend.iter().enumerate() {
+        for (col_idx, cell) in row_data.iter().enumerate() {
+            let row = (row_idx + 1) as u32;
+            let col = col_idx as u16;
+
+            // Columna B (índice 1) es Monto - escribir como número
+            if col_idx == 1 {
+                if let Some(monto_num) = monto_rend(cell) {
+                    worksheet_rend.write_number_with_format(row, col, monto_num, &formato_pesos)?;
+                } else {
+                    worksheet_rend.write_string(row, col, cell)?;
+                }
+            } else {
+                worksheet_rend.write_string(row, col, cell)?;
+            }
+        }
+
+        // Columna F (índice 5) es Observaciones - avisar cheques duplicados y cruces
+        let cheque = row_data.first().map(String::as_str).unwrap_or("");
+        let cruce = cruces
+            .get(row_idx)
+            .copied()
+            .flatten()
+            .map(|i| disposicion.numero_fila(i) as usize);
+        if let Some(observacion) = observaciones_rend(row_data, duplicados.contains(cheque), cruce)
+        {
+            worksheet_rend.write_string((row_idx + 1) as u32, 5, &observacion)?;
+        }
+        max_row_rend = (row_idx + 1) as u32;
+    }
+
+    // Agregar fórmulas a REND (si hay datos)
+    if max_row_rend > 0 {
+        // Columnas de la hoja PDF que consultan las fórmulas
+        let expediente = disposicion.letra(ColumnaPdf::Expediente);
+        let año = disposicion.letra(ColumnaPdf::Año);
+        let monto = disposicion.letra(ColumnaPdf::Monto);
+        let cheque = disposicion.letra(ColumnaPdf::Cheque);
+
+        for row in 1..=max_row_rend {
+            let indice = row as usize - 1;
+            let cruce = cruces.get(indice).copied().flatten();
+            let resultados = resultados_rend(datos, &datos_rend[indice], cruce);
+            let año_visible = resultados.año.map(|a| a.to_string()).unwrap_or_default();
+
+            // Fila cruzada por aproximación: las fórmulas apuntan a su registro
+            let (formula_d, formula_e, formula_h) = if let Some(i) = cruce {
+                let fila_pdf = disposicion.numero_fila(i);
+                (
+                    format!("=PDF!${}${}", expediente, fila_pdf),
+                    format!("=IF(PDF!${a}${0}>0,PDF!${a}${0},\"\")", fila_pdf, a = año),
+                    format!(
+                        "=COUNTIF(PDF!${ch}:${ch},PDF!${ch}${0})",
+                        fila_pdf,
+                        ch = cheque
+                    ),
+                )
+            } else {
+                (
+                    format!(
+                        "=IFERROR(INDEX(PDF!${exp}:${exp},MATCH(A{0},PDF!${ch}:${ch},0)),INDEX(PDF!${exp}:${exp},MATCH(B{0},PDF!${m}:${m},0)))",
+                        row + 1,
+                        exp = expediente,
+                        ch = cheque,
+                        m = monto
+                    ),
+                    format!(
+                        "=IFERROR(IF(INDEX(PDF!${a}:${a},MATCH(A{0},PDF!${ch}:${ch},0))>0,INDEX(PDF!${a}:${a},MATCH(A{0},PDF!${ch}:${ch},0)),\"\"),IF(INDEX(PDF!${a}:${a},MATCH(B{0},PDF!${m}:${m},0))>0,INDEX(PDF!${a}:${a},MATCH(B{0},PDF!${m}:${m},0)),\"\"))",
+                        row + 1,
+                        a = año,
+                        ch = cheque,
+                        m = monto
+                    ),
+                    format!("=COUNTIF(PDF!${ch}:${ch},A{0})", row + 1, ch = cheque),
+                )
+            };
+            // Sin registro en la hoja PDF se conserva el expediente que tenía la fila
+            let formula_d = match &resultados.expediente_previo {
+                Some(previo) => format!(
+                    "=IFERROR({},\"{}\")",
+                    &formula_d[1..],
+                    previo.replace('"', "\"\"")
+                ),
+                None => formula_d,
+            };
+            let formula_g = format!("=COUNTIF(PDF!${m}:${m},B{0})", row + 1, m = monto);
+
+            // D: Expediente, E: Año, G: Control, H: Control cheque, con su resultado
+            // para que la próxima corrida lea el expediente
+            let formulas = [
+                (3, formula_d, resultados.expediente.clone()),
+                (4, formula_e, año_visible),
+                (6, formula_g, resultados.por_monto.to_string()),
+                (7, formula_h, resultados.por_cheque.to_string()),
+            ];
+            for (col, formula, resultado) in formulas {
+                worksheet_rend.write_formula(
+                    row,
+                    col,
+                    Formula::new(&formula).set_result(resultado),
+                )?;
+            }
+        }
+
+        // Crear tabla REND, incluyendo las columnas agregadas por el usuario,
+        // con una fila de totales que suma el Monto de las filas visibles (SUBTOTAL 109)
+        let columnas: Vec<TableColumn> = headers_rend
+            .iter()
+            .enumerate()
+            .map(|(col, h)| {
+                let columna = TableColumn::new().set_header(h);
+                match col {
+                    0 => columna.set_total_label(ETIQUETA_TOTAL),
+                    1 => columna
+                        .set_total_function(TableFunction::Sum)
+                        .set_format(&formato_pesos),
+                    _ => columna,
+                }
+            })
+            .collect();
+        let table_rend = Table::new()
+            .set_name(TABLA_REND)
+            .set_style(estilo)
+            .set_columns(&columnas)
+            .set_total_row(true);
+        let ultima_columna = (headers_rend.len() - 1) as u16;
+        worksheet_rend.add_table(0, 0, max_row_rend + 1, ultima_columna, &table_rend)?;
+    }
+
+    // Anchos de REND: la columna Expediente muestra los expedientes de la hoja PDF
+    let mut anchos_rend = medir_columnas(
+        headers_rend.iter().map(String::as_str),
+        datos_rend.iter().map(|fila| {
+            let mut fila = fila.clone();
+            if let Some(monto) = fila.get_mut(1) {
+                *monto = monto_visible(&monto.replace(',', "."), MONEDA_PESOS);
+            }
+            fila
+        }),
+    );
+    if let Some(ancho) = anchos_rend.get_mut(3) {
+        let expediente_max = datos.iter().map(|d| d.expediente.chars().count()).max();
+        *ancho = (*ancho).max(expediente_max.unwrap_or_default());
+    }
+    ajustar_anchos(worksheet_rend, &anchos_rend)?;
+
+    Ok(())
+}
+
+/// Origen de los datos para las propiedades del documento (la ruta indicada en
+/// `opciones.origen` o, si no hay, los PDF) y los PDF de los registros, sin repetir.
+pub(crate) fn origen(datos: &[DatosPagina], opciones: &OpcionesExcel) -> (String, String) {
+    let mut vistos = HashSet::new();
+    let archivos: Vec<&str> = datos
+        .iter()
+        .map(|d| d.archivo.as_str())
+        .filter(|a| vistos.insert(*a))
+        .collect();
+    let archivos = archivos.join(", ");
+    let origen = opciones.origen.clone().unwrap_or_else(|| archivos.clone());
+    (origen, archivos)
+}
+
+/// Propiedades del documento para auditar de dónde y cuándo salió el archivo:
+/// origen, fecha de proceso, versión del programa y cantidad de registros.
+fn propiedades(datos: &[DatosPagina], opciones: &OpcionesExcel) -> DocProperties {
+    let ahora = chrono::Utc::now();
+    let version = crate::version();
+    let (origen, archivos) = origen(datos, opciones);
+
+    DocProperties::new()
+        .set_title(format!("Procuración - {}", origen))
+        .set_comment(format!(
+            "Generado por pdf-procuracion {} el {} a partir de {} ({} registros)",
+            version,
+            ahora
+                .with_timezone(&chrono::Local)
+                .format("%d/%m/%Y %H:%M:%S"),
+            origen,
+            datos.len()
+        ))
+        .set_creation_datetime(&ahora)
+        .set_custom_property("Origen", origen.as_str())
+        .set_custom_property("Archivos", archivos.as_str())
+        .set_custom_property("Versión", version.as_str())
+        .set_custom_property("Registros", datos.len() as i32)
+        .set_custom_property("Procesado", &ahora)
+}
+
+/// Escribe las hojas REND y PDF con sus fórmulas y tablas.
+///
+/// Con `opciones.sin_rend` solo se escribe la hoja PDF, sin las fórmulas de control.
+fn escribir_excel(
+    planilla: &Planilla,
+    output_path: &Path,
+    opciones: &OpcionesExcel,
+) -> Result<(), XlsxError> {
+    let datos = &planilla.datos;
+    let cruces = &planilla.cruces;
+    let estilo = opciones
+        .estilo_tabla
+        .as_deref()
+        .and_then(estilo_tabla)
+        .unwrap_or(TableStyle::Light1);
+    let disposicion = Disposicion::desde_opciones(opciones);
+    let mut workbook = Workbook::new();
+    workbook.set_properties(&propiedades(datos, opciones));
+
+    if !opciones.sin_rend {
+        escribir_rend(
+            &mut workbook,
+            datos,
+            &planilla.encabezados_rend,
+            &planilla.filas_rend,
+            cruces,
+            estilo,
+            &disposicion,
+        )?;
+    }
+
+    // Fila de REND con la que se cruzó cada registro por aproximación
+    let mut cruzados = HashMap::new();
+    for (fila, cruce) in cruces.iter().enumerate() {
+        if let Some(i) = cruce {
+            cruzados.entry(*i).or_insert(fila + 2);
+        }
+    }
+
+    // Crear hoja PDF
+    let worksheet_pdf = workbook.add_worksheet();
+    worksheet_pdf.set_name("PDF")?;
+
+    // Encabezados PDF; los campos de los extractores propios van al final
+    let extra = nombres_campos_extra(datos);
+    let mut headers_pdf = disposicion.ordenar(&opciones.idioma.columnas_pdf());
+    headers_pdf.extend(&extra);
+
+    // Título o membrete de la plantilla, sobre los encabezados
+    for (fila, col, texto) in &planilla.membrete_pdf {
+        worksheet_pdf.write_string(*fila, *col, texto)?;
+    }
+
+    let fila_encabezados = disposicion.fila_encabezados();
+    for (col, header) in headers_pdf.iter().enumerate() {
+        worksheet_pdf.write_string(fila_encabezados, col as u16, *header)?;
+    }
+
+    let formato_fecha = Format::new().set_num_format("dd/mm/yyyy");
+
+    // Escribir datos extraídos del PDF
+    for (row_idx, dato) in datos.iter().enumerate() {
+        let row = disposicion.fila(row_idx);
+
+        // Los campos de baja confianza se resaltan para dirigir la revisión
+        let confianza = &dato.confianza;
+        let col = |columna| disposicion.col(columna);
+        worksheet_pdf.write_string_with_format(
+            row,
+            col(ColumnaPdf::Nombre),
+            &dato.nombre,
+            &resaltar(Format::new(), confianza.nombre),
+        )?;
+        worksheet_pdf.write_string_with_format(
+            row,
+            col(ColumnaPdf::Expediente),
+            &dato.expediente,
+            &resaltar(Format::new(), confianza.expediente),
+        )?;
+
+        // Escribir año como número si es posible
+        let formato_año = resaltar(Format::new(), confianza.año);
+        let col_año = col(ColumnaPdf::Año);
+        if let Ok(año_num) = dato.año.trim().parse::<f64>() {
+            worksheet_pdf.write_number_with_format(row, col_año, año_num, &formato_año)?;
+        } else {
+            worksheet_pdf.write_string_with_format(row, col_año, &dato.año, &formato_año)?;
+        }
+
+        // Escribir monto como número si es posible
+        let formato = resaltar(formato_monto(&dato.moneda), confianza.monto);
+        let col_monto = col(ColumnaPdf::Monto);
+        if let Ok(monto_num) = dato.monto.parse::<f64>() {
+            worksheet_pdf.write_number_with_format(row, col_monto, monto_num, &formato)?;
+        } else {
+            worksheet_pdf.write_string_with_format(row, col_monto, &dato.monto, &formato)?;
+        }
+
+        // Texto original del monto, para revisar la normalización a simple vista
+        worksheet_pdf.write_string(row, col(ColumnaPdf::MontoOriginal), &dato.monto_original)?;
+
+        worksheet_pdf.write_string_with_format(
+            row,
+            col(ColumnaPdf::Cheque),
+            &dato.cheque,
+            &resaltar(Format::new(), confianza.cheque),
+        )?;
+
+        // Fórmulas de control; sin hoja REND las columnas quedan vacías
+        if !opciones.sin_rend {
+            let formula_f = format!(
+                "=COUNTIF(REND!$B:$B,{}{})",
+                disposicion.letra(ColumnaPdf::Monto),
+                row + 1
+            );
+            // Un registro cruzado por aproximación se cuenta con el cheque de su fila de REND
+            let formula_g = match cruzados.get(&row_idx) {
+                Some(fila_rend) => format!("=COUNTIF(REND!$A:$A,REND!$A${})", fila_rend),
+                None => format!(
+                    "=COUNTIF(REND!$A:$A,{}{})",
+                    disposicion.letra(ColumnaPdf::Cheque),
+                    row + 1
+                ),
+            };
+            worksheet_pdf.write_formula(row, col(ColumnaPdf::Control), Formula::new(&formula_f))?;
+            worksheet_pdf.write_formula(
+                row,
+                col(ColumnaPdf::ControlCheque),
+                Formula::new(&formula_g),
+            )?;
+        }
+
+        worksheet_pdf.write_string(row, col(ColumnaPdf::Archivo), &dato.archivo)?;
+        worksheet_pdf.write_string(
+            row,
+            col(ColumnaPdf::Observaciones),
+            dato.observaciones.join("; "),
+        )?;
+        worksheet_pdf.write_string(row, col(ColumnaPdf::Moneda), &dato.moneda)?;
+        worksheet_pdf.write_string(row, col(ColumnaPdf::Juzgado), &dato.juzgado)?;
+
+        // Fecha como valor de fecha real para poder ordenar; vacía si no se encontró
+        if let Some(fecha) = &dato.fecha {
+            worksheet_pdf.write_datetime_with_format(
+                row,
+                col(ColumnaPdf::Fecha),
+                fecha,
+                &formato_fecha,
+            )?;
+        }
+
+        // Página de origen como número, para ordenar y ubicar el registro en el PDF
+        worksheet_pdf.write_number(row, col(ColumnaPdf::Pagina), dato.pagina as f64)?;
+
+        for (i, nombre) in extra.iter().enumerate() {
+            let col_extra = (ColumnaPdf::TODAS.len() + i) as u16;
+            worksheet_pdf.write_string(row, col_extra, dato.campo_extra(nombre))?;
+        }
+    }
+
+    // Crear tabla PDF
+    if !datos.is_empty() {
+        let max_row_pdf = disposicion.fila(datos.len() - 1);
+        let columnas: Vec<TableColumn> = headers_pdf
+            .iter()
+            .map(|h| TableColumn::new().set_header(*h))
+            .collect();
+        let table_pdf = Table::new()
+            .set_name(TABLA_PDF)
+            .set_style(estilo)
+            .set_columns(&columnas);
+        let ultima_columna = (headers_pdf.len() - 1) as u16;
+        worksheet_pdf.add_table(fila_encabezados, 0, max_row_pdf, ultima_columna, &table_pdf)?;
+    }
+
+    let anchos_pdf = medir_columnas(
+        headers_pdf.iter().copied(),
+        datos.iter().map(|dato| {
+            let mut textos = disposicion.ordenar(&textos_pdf(dato));
+            textos.extend(
+                extra
+                    .iter()
+                    .map(|nombre| dato.campo_extra(nombre).to_string()),
+            );
+            textos
+        }),
+    );
+    ajustar_anchos(worksheet_pdf, &anchos_pdf)?;
+
+    workbook.save(output_path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extraccion::MONEDA_PESOS;
+
+    #[test]
+    fn cruce_por_expediente_se_mantiene_al_volver_a_generar() {
+        let ruta = std::env::temp_dir().join(format!("cruce-rend-{}.xlsx", std::process::id()));
+        // Fila de REND que solo coincide con el registro por el expediente de la columna D
+        let mut libro = rust_xlsxwriter::Workbook::new();
+        let rend = libro.add_worksheet().set_name("REND").unwrap();
+        for (col, valor) in ["Cheque", "Monto", "Autos", "Expediente"]
+            .iter()
+            .enumerate()
+        {
+            rend.write_string(0, col as u16, *valor).unwrap();
+        }
+        rend.write_string(1, 0, "zzz").unwrap();
+        rend.write_number(1, 1, 7.0).unwrap();
+        rend.write_string(1, 2, "z").unwrap();
+        rend.write_string(1, 3, "Expte. 0123").unwrap();
+        libro.save(&ruta).unwrap();
+
+        let datos = [DatosPagina {
+            nombre: "PEREZ JUAN s/ COBRO".to_string(),
+            expediente: "EXP-123".to_string(),
+            año: "2021".to_string(),
+            monto: "1000.00".to_string(),
+            monto_original: "$1.000,00".to_string(),
+            cheque: "CH 12345678".to_string(),
+            moneda: MONEDA_PESOS.to_string(),
+            juzgado: String::new(),
+            fecha: None,
+            archivo: "a.pdf".to_string(),
+            pagina: 1,
+            observaciones: Vec::new(),
+            confianza: ConfianzaCampos::default(),
+            patron_expediente: None,
+            campos_extra: Vec::new(),
+        }];
+        let opciones = OpcionesExcel {
+            cruce_aproximado: true,
+            ..OpcionesExcel::default()
+        };
+        // La segunda corrida lee el expediente que escribió la primera
+        for _ in 0..2 {
+            guardar_y_formatear_excel(&datos, &ruta, &opciones).unwrap();
+            let mut libro: Xlsx<_> = open_workbook(&ruta).unwrap();
+            let rend = libro.worksheet_range("REND").unwrap();
+            let celda = |col| {
+                rend.get((1, col))
+                    .map(|c| c.to_string())
+                    .unwrap_or_default()
+            };
+            assert_eq!(celda(3), "EXP-123");
+            assert_eq!(celda(5), "Aviso: cruce aproximado (fila 2 de la hoja PDF)");
+        }
+        std::fs::remove_file(&ruta).unwrap();
+    }
+}
@@ -0,0 +1,192 @@
+//! Exportación de los datos extraídos a formatos distintos de Excel.
+
+use crate::excel::{ColumnaPdf, Idioma};
+use crate::{nombres_campos_extra, Confianza, DatosPagina, ProcError};
+use chrono::NaiveDate;
+use serde::{Serialize, Serializer};
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// Asocia la ruta de salida a un error de escritura.
+fn error_exportar(path: &Path) -> impl FnOnce(io::Error) -> ProcError + '_ {
+    move |source| ProcError::Exportar {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+/// Guarda los datos como un arreglo JSON con formato legible.
+pub fn guardar_json(datos: &[DatosPagina], path: &Path) -> Result<(), ProcError> {
+    escribir_json(datos, path).map_err(error_exportar(path))
+}
+
+fn escribir_json(datos: &[DatosPagina], path: &Path) -> io::Result<()> {
+    let archivo = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(archivo, datos)?;
+    Ok(())
+}
+
+/// Registro del JSON plano: claves ASCII, sin objetos anidados y cada campo
+/// siempre del mismo tipo o `null`.
+#[derive(Serialize)]
+struct RegistroPlano<'a> {
+    nombre: Option<&'a str>,
+    expediente: Option<&'a str>,
+    anio: Option<i32>,
+    monto: Option<f64>,
+    monto_original: Option<&'a str>,
+    moneda: Option<&'a str>,
+    cheque: Option<&'a str>,
+    juzgado: Option<&'a str>,
+    fecha: Option<NaiveDate>,
+    archivo: &'a str,
+    pagina: usize,
+    observaciones: String,
+    confianza_nombre: Confianza,
+    confianza_expediente: Confianza,
+    confianza_anio: Confianza,
+    confianza_monto: Confianza,
+    confianza_cheque: Confianza,
+    patron_expediente: Option<usize>,
+    /// Campos de los extractores propios, como `extra_<nombre>`
+    #[serde(flatten, serialize_with = "serializar_extra_plano")]
+    extra: Vec<(String, Option<&'a str>)>,
+}
+
+fn serializar_extra_plano<S: Serializer>(
+    campos: &[(String, Option<&str>)],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(campos.iter().map(|(clave, valor)| (clave, valor)))
+}
+
+/// Nombre de campo en ASCII y minúsculas, con guiones bajos en lugar de los
+/// demás caracteres ("Número de póliza" → "numero_de_poliza").
+fn clave_ascii(nombre: &str) -> String {
+    nombre
+        .nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+impl<'a> RegistroPlano<'a> {
+    fn new(dato: &'a DatosPagina, extra: &[&str]) -> Self {
+        let confianza = &dato.confianza;
+        // Los valores de respaldo (el número de página) y los vacíos van como null
+        let encontrado = |valor: &'a str, campo: Confianza| {
+            (campo != Confianza::Respaldo && !valor.is_empty()).then_some(valor)
+        };
+        let no_vacio = |valor: &'a str| (!valor.is_empty()).then_some(valor);
+        RegistroPlano {
+            nombre: encontrado(&dato.nombre, confianza.nombre),
+            expediente: encontrado(&dato.expediente, confianza.expediente),
+            anio: dato.año.trim().parse().ok(),
+            monto: encontrado(&dato.monto, confianza.monto).and_then(|m| m.parse().ok()),
+            monto_original: no_vacio(&dato.monto_original),
+            moneda: no_vacio(&dato.moneda),
+            cheque: encontrado(&dato.cheque, confianza.cheque),
+            juzgado: no_vacio(&dato.juzgado),
+            fecha: dato.fecha,
+            archivo: &dato.archivo,
+            pagina: dato.pagina,
+            observaciones: dato.observaciones.join("; "),
+            confianza_nombre: confianza.nombre,
+            confianza_expediente: confianza.expediente,
+            confianza_anio: confianza.año,
+            confianza_monto: confianza.monto,
+            confianza_cheque: confianza.cheque,
+            patron_expediente: dato.patron_expediente,
+            extra: extra
+                .iter()
+                .map(|nombre| {
+                    let clave = format!("extra_{}", clave_ascii(nombre));
+                    (clave, no_vacio(dato.campo_extra(nombre)))
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Guarda los datos como un arreglo JSON plano, pensado para cargarlo como tabla
+/// (por ejemplo con `pandas.read_json`).
+///
+/// A diferencia de [`guardar_json`], las claves son ASCII, no hay objetos
+/// anidados y cada campo tiene siempre el mismo tipo: el monto y el año son
+/// números, y los campos no encontrados son `null` en lugar del número de página.
+/// Todos los registros tienen las mismas claves, incluidas las de los campos
+/// adicionales.
+pub fn guardar_json_plano(datos: &[DatosPagina], path: &Path) -> Result<(), ProcError> {
+    escribir_json_plano(datos, path).map_err(error_exportar(path))
+}
+
+fn escribir_json_plano(datos: &[DatosPagina], path: &Path) -> io::Result<()> {
+    let extra = nombres_campos_extra(datos);
+    let registros: Vec<RegistroPlano> = datos
+        .iter()
+        .map(|dato| RegistroPlano::new(dato, &extra))
+        .collect();
+    let archivo = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(archivo, &registros)?;
+    Ok(())
+}
+
+/// Guarda los datos como CSV, una fila por página, con los encabezados de la hoja PDF
+/// salvo las columnas de control, que son fórmulas.
+///
+/// Los campos de los extractores propios van en columnas al final.
+///
+/// El monto ya viene normalizado con punto decimal desde `extraer_monto`.
+pub fn guardar_csv(datos: &[DatosPagina], path: &Path) -> Result<(), ProcError> {
+    escribir_csv(datos, path).map_err(error_exportar(path))
+}
+
+fn escribir_csv(datos: &[DatosPagina], path: &Path) -> io::Result<()> {
+    let mut writer = csv::Writer::from_path(path)?;
+    let extra = nombres_campos_extra(datos);
+    let columnas: Vec<ColumnaPdf> = ColumnaPdf::TODAS
+        .into_iter()
+        .filter(|c| !matches!(c, ColumnaPdf::Control | ColumnaPdf::ControlCheque))
+        .collect();
+    let nombres = Idioma::Español.columnas_pdf();
+    let encabezados = columnas.iter().map(|&c| nombres[c as usize]);
+    writer.write_record(encabezados.chain(extra.iter().copied()))?;
+
+    for dato in datos {
+        let campos = columnas.iter().map(|&columna| valor_csv(dato, columna));
+        let valores = extra
+            .iter()
+            .map(|nombre| dato.campo_extra(nombre).to_string());
+        writer.write_record(campos.chain(valores))?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Valor de una columna de la hoja PDF en el CSV; la fecha va en formato ISO.
+fn valor_csv(dato: &DatosPagina, columna: ColumnaPdf) -> String {
+    match columna {
+        ColumnaPdf::Nombre => dato.nombre.clone(),
+        ColumnaPdf::Expediente => dato.expediente.clone(),
+        ColumnaPdf::Año => dato.año.trim().to_string(),
+        ColumnaPdf::Monto => dato.monto.clone(),
+        ColumnaPdf::MontoOriginal => dato.monto_original.clone(),
+        ColumnaPdf::Cheque => dato.cheque.clone(),
+        ColumnaPdf::Control | ColumnaPdf::ControlCheque => String::new(),
+        ColumnaPdf::Archivo => dato.archivo.clone(),
+        ColumnaPdf::Observaciones => dato.observaciones.join("; "),
+        ColumnaPdf::Moneda => dato.moneda.clone(),
+        ColumnaPdf::Juzgado => dato.juzgado.clone(),
+        ColumnaPdf::Fecha => dato.fecha.map(|f| f.to_string()).unwrap_or_default(),
+        ColumnaPdf::Pagina => dato.pagina.to_string(),
+    }
+}
@@ -0,0 +1,1463 @@
+//! Funciones de extracción de campos a partir del texto de una página.
+//!
+//! Cada extractor recibe el texto ya limpio de la página y el índice `p`
+//! (base cero) de la página; cuando no encuentra el dato devuelve el número
+//! de página como valor de respaldo.
+
+use crate::Config;
+use chrono::NaiveDate;
+use regex::Regex;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+/// Patrón del nombre: texto entre comillas que sigue a "autos".
+pub const PATRON_AUTOS: &str = r#"autos\s+"(.*?)""#;
+
+/// Patrones de expediente, en orden de prioridad.
+///
+/// Sin lookbehind (incompatible con regex de Rust).
+pub const PATRONES_EXPEDIENTE: [&str; 6] = [
+    r"[Ee][Xx][Pp]\-[^,]*,",
+    r"[Ee][Xx][Pp]\.[^,]*,",
+    r"[Ee][Xx][Pp] [^,]*,",
+    r"\d{4,6}-\d{4}",
+    r"\d{4,6}/\d{4}",
+    r"[Ee][Jj][Ff]\-[^,]*,",
+];
+
+/// Patrón relajado de expediente, probado solo si fallan todos los demás:
+/// cualquier número seguido de "/" o "-" y de otro número corto ("4567/19").
+pub const PATRON_EXPEDIENTE_RELAJADO: &str = r"\b\d{3,8}[-/]\d{2,4}\b";
+
+/// Patrón del monto entre paréntesis: `($...)`.
+pub const PATRON_MONTO: &str = r"\(\$([^)]+)\)";
+
+/// Patrón del monto en dólares: "U$S 1.500,00", "U$D 200" o "USD 1500".
+pub const PATRON_MONTO_DOLARES: &str = r"(?:U\$S|U\$D|USD)\s*(\d[\d.,]*)";
+
+/// Patrón del juzgado con su número: "Juzgado de Primera Instancia Nro. 5", sobre
+/// el texto ya pasado por [`normalizar_nro`].
+pub const PATRON_JUZGADO: &str = r"(?i)\bjuzgado\b[^,;]{0,100}?\bN(?:ro\.)?\s*\d+";
+
+/// Patrón del juzgado sin número, hasta el primer signo de puntuación.
+pub const PATRON_JUZGADO_SIN_NUMERO: &str = r"(?i)\bjuzgado\b[^,;.]{0,80}";
+
+/// Patrón de fecha en formato largo: "15 de marzo de 2024".
+pub const PATRON_FECHA: &str = r"(?i)\b(\d{1,2})\s+de\s+(enero|febrero|marzo|abril|mayo|junio|julio|agosto|septiembre|setiembre|octubre|noviembre|diciembre)\s+(?:de|del)\s+(\d{4})\b";
+
+/// Código de moneda para montos en pesos.
+pub const MONEDA_PESOS: &str = "ARS";
+
+/// Código de moneda para montos en dólares.
+pub const MONEDA_DOLARES: &str = "USD";
+
+/// Patrón del monto en letras: "PESOS DOS MIL", opcionalmente "CON 50/100".
+pub const PATRON_MONTO_PALABRAS: &str =
+    r"(?i)\bpesos\s+([a-záéíóúñ\s]+?)(?:\s+con\s+(\d{1,2})/100|[^a-záéíóúñ\s]|$)";
+
+/// Patrones de cheque, aplicados sobre el texto pasado por [`normalizar_nro`] y
+/// sin puntos, guiones ni espacios.
+pub const PATRONES_CHEQUE: [&str; 1] = [r"ChequeNro(\d+)"];
+
+/// Patrón de transferencias ITB sobre el texto pasado por [`normalizar_nro`] y
+/// sin puntos, guiones ni espacios.
+pub const PATRON_ITB: &str = r"ITBNro:(\d+)";
+
+/// Patrón del número interno (ITB o MEP) sobre el texto sin puntos, guiones ni espacios.
+pub const PATRON_INTERNO: &str = r"INTERNO:(\d+)";
+
+/// Dígitos finales del número INTERNO que no forman parte del número de operación.
+const SUFIJO_INTERNO: usize = 4;
+
+/// Dígitos mínimos que deben quedar del número INTERNO al quitarle el sufijo;
+/// con menos, el número está cortado o mal leído y se descarta.
+const MIN_DIGITOS_INTERNO: usize = 2;
+
+/// Número de cheque más chico que se considera verosímil; los cheques se emiten
+/// con numeración de ocho dígitos, así que uno menor casi seguro está mal leído.
+pub const MIN_NUMERO_CHEQUE: u64 = 1000;
+
+// Expresiones compiladas una sola vez y compartidas entre hilos.
+// Todas las formas de escribir "número" que aparecen en las órdenes: "Nº" y
+// "N°" (también con el símbolo mal decodificado como "NÂº"), "N.º", "Nro" y "nro."
+// El "Nro" termina la palabra o sigue pegado al número, que se conserva en el grupo
+static RE_NRO: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b[Nn][Rr][Oo][Ss]?(?:\b\.?|(\d))|[Nn]\s?\.?\s?Â?[º°˚ᵒ]\.?").unwrap()
+});
+// La palabra se reemplaza por "EXP-" junto con el "Nro." que la sigue
+static RE_EXPEDIENTE_PALABRA: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)expediente(?:\s*Nro\.)?\s*").unwrap());
+static RE_EXPTE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)Expte\.(?:\s*Nro\.)?\s*").unwrap());
+static RE_ESPACIOS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\s+").unwrap());
+static RE_SEPARADOR_EXPEDIENTE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)(\d|exp)\s*([-/])\s*(\d)").unwrap());
+static RE_EXPEDIENTES: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    PATRONES_EXPEDIENTE
+        .iter()
+        .map(|p| Regex::new(p).unwrap())
+        .collect()
+});
+static RE_EXPEDIENTE_RELAJADO: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(PATRON_EXPEDIENTE_RELAJADO).unwrap());
+static RE_MONTO: LazyLock<Regex> = LazyLock::new(|| Regex::new(PATRON_MONTO).unwrap());
+static RE_ESPACIOS_MONTO: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\(\s*(-?)\s*\$\s*").unwrap());
+static RE_CHEQUES: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    PATRONES_CHEQUE
+        .iter()
+        .map(|p| Regex::new(p).unwrap())
+        .collect()
+});
+static RE_MONTO_DOLARES: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(PATRON_MONTO_DOLARES).unwrap());
+static RE_MONTO_PALABRAS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(PATRON_MONTO_PALABRAS).unwrap());
+static RE_JUZGADO: LazyLock<Regex> = LazyLock::new(|| Regex::new(PATRON_JUZGADO).unwrap());
+static RE_JUZGADO_SIN_NUMERO: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(PATRON_JUZGADO_SIN_NUMERO).unwrap());
+static RE_FECHA: LazyLock<Regex> = LazyLock::new(|| Regex::new(PATRON_FECHA).unwrap());
+static RE_ITB: LazyLock<Regex> = LazyLock::new(|| Regex::new(PATRON_ITB).unwrap());
+static RE_INTERNO: LazyLock<Regex> = LazyLock::new(|| Regex::new(PATRON_INTERNO).unwrap());
+
+/// Lleva a "Nro." las distintas formas de abreviar "número" ("Nº", "N°", "N.º",
+/// "Nro", "nro."), para que los patrones solo tengan que reconocer una.
+///
+/// El "°" no es una letra y la limpieza del texto lo descarta, mientras que el "º"
+/// sí lo es y se conserva; sin normalizar, "N° 1234" y "Nº 1234" terminan distintos.
+pub fn normalizar_nro(texto: &str) -> Cow<'_, str> {
+    RE_NRO.replace_all(texto, "Nro.$1")
+}
+
+/// Patrón del nombre entre comillas que sigue a la palabra `clave`.
+pub fn patron_nombre(clave: &str) -> String {
+    format!(r#"{}\s+"(.*?)""#, regex::escape(clave))
+}
+
+/// Extrae el texto entre comillas dobles que sigue a la palabra "autos".
+///
+/// Las palabras clave de `config.patrones_nombre` se prueban en orden y gana la
+/// primera que encuentra un nombre. El nombre se limpia con [`limpiar_nombre`];
+/// los textos que coinciden con `config.frases_descartadas` se saltean.
+pub fn extraer_texto_entre_comillas(texto: &str, p: usize, config: &Config) -> String {
+    nombres_entre_comillas(texto, config)
+        .next()
+        .unwrap_or_else(|| (p + 1).to_string())
+}
+
+/// Extrae todos los nombres entre comillas de la página, en el orden en que se prueban.
+///
+/// Sirve para las páginas consolidadas donde un mismo cheque cubre varias
+/// carátulas. Una carátula citada dos veces, o encontrada por dos patrones,
+/// aparece una sola vez. Devuelve una lista vacía si no encuentra ninguno.
+pub fn extraer_textos_entre_comillas(texto: &str, config: &Config) -> Vec<String> {
+    let mut vistos = HashSet::new();
+    nombres_entre_comillas(texto, config)
+        .filter(|nombre| vistos.insert(nombre.clone()))
+        .collect()
+}
+
+fn nombres_entre_comillas<'a>(
+    texto: &'a str,
+    config: &'a Config,
+) -> impl Iterator<Item = String> + 'a {
+    config
+        .patrones_nombre
+        .iter()
+        .flat_map(move |patron| patron.captures_iter(texto))
+        .filter_map(|c| c.get(1).map(|m| limpiar_nombre(m.as_str(), config)))
+        .filter(|nombre| {
+            !config
+                .frases_descartadas
+                .iter()
+                .any(|frase| frase.eq_ignore_ascii_case(nombre))
+        })
+}
+
+/// Recorta el nombre a `largo_max` caracteres, terminando en "…".
+///
+/// Devuelve `None` si el nombre ya entra en ese largo.
+pub fn acortar_nombre(nombre: &str, largo_max: usize) -> Option<String> {
+    if nombre.chars().count() <= largo_max {
+        return None;
+    }
+    let acortado: String = nombre.chars().take(largo_max.saturating_sub(1)).collect();
+    Some(format!("{}…", acortado.trim_end()))
+}
+
+/// Quita espacios sobrantes y los finales de `config.sufijos_nombre`.
+///
+/// Los sufijos se comparan sin distinguir mayúsculas y se quitan repetidamente,
+/// de modo que "PEREZ JUAN y otros s/ COBRO" pierde ambos si están configurados.
+pub fn limpiar_nombre(nombre: &str, config: &Config) -> String {
+    let mut nombre = nombre.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    loop {
+        let sufijo = config.sufijos_nombre.iter().find(|sufijo| {
+            let sufijo = sufijo.trim();
+            let inicio = nombre.len().saturating_sub(sufijo.len());
+            !sufijo.is_empty()
+                && nombre.len() > sufijo.len()
+                && nombre.is_char_boundary(inicio)
+                && nombre[inicio..].eq_ignore_ascii_case(sufijo)
+        });
+        match sufijo {
+            Some(sufijo) => {
+                let largo = nombre.len() - sufijo.trim().len();
+                nombre.truncate(largo);
+                nombre.truncate(nombre.trim_end().len());
+            }
+            None => break,
+        }
+    }
+
+    nombre
+}
+
+/// Qué tan confiable es un valor extraído.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Confianza {
+    /// El patrón encontró un único valor
+    #[default]
+    Encontrado,
+    /// Se encontró un valor pero hay otros candidatos o el valor es dudoso
+    Ambiguo,
+    /// No se encontró nada y se usó el número de página como respaldo
+    Respaldo,
+}
+
+/// Expediente extraído de una página, con el control del año.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expediente {
+    pub numero: String,
+    pub año: String,
+    /// Algún patrón encontró el expediente; si no, `numero` es el número de página
+    pub encontrado: bool,
+    /// El año no estaba separado del número por "/" o "-", así que puede ser
+    /// parte de otro número mal leído
+    pub año_dudoso: bool,
+    /// El expediente termina en "/aaaa" o "-aaaa" pero ese año está fuera de
+    /// `config.año_min..=config.año_max`, así que queda como parte del número
+    pub año_fuera_de_rango: bool,
+    /// Se encontró con [`PATRON_EXPEDIENTE_RELAJADO`], así que puede no ser el expediente
+    pub relajado: bool,
+    /// Posición del patrón que encontró el expediente: primero los de
+    /// [`PATRONES_EXPEDIENTE`] y luego los de `config.patrones_expediente`.
+    /// `None` si no se encontró o se encontró con el patrón relajado
+    pub patron: Option<usize>,
+}
+
+/// Extrae el número de expediente y el año del texto.
+///
+/// Equivale a [`extraer_expediente`] descartando el control del año.
+pub fn extraer_expediente_y_año(texto: &str, p: usize, config: &Config) -> (String, String) {
+    let expediente = extraer_expediente(texto, p, config);
+    (expediente.numero, expediente.año)
+}
+
+/// Extrae el número de expediente y el año del texto.
+///
+/// Se prueban los patrones incorporados y luego los de `config.patrones_expediente`.
+/// El año solo se separa si cae dentro del rango `config.año_min..=config.año_max`,
+/// y se marca como dudoso si no es el último tramo del expediente.
+/// Con `config.expediente_relajado`, si ningún patrón coincide se prueba
+/// [`PATRON_EXPEDIENTE_RELAJADO`] y el resultado se marca como `relajado`.
+pub fn extraer_expediente(texto: &str, p: usize, config: &Config) -> Expediente {
+    // Normalizar texto
+    let texto = normalizar_nro(texto);
+    let texto = RE_EXPEDIENTE_PALABRA
+        .replace_all(&texto, "EXP-")
+        .to_string();
+    let texto = RE_EXPTE.replace_all(&texto, "EXP-").to_string();
+    // Espaciado irregular: "EXP - 1234 / 2020" → "EXP-1234/2020"
+    let mut texto = RE_ESPACIOS.replace_all(&texto, " ").to_string();
+    // Las coincidencias no se superponen: en "1 - 2 - 3" la segunda comparte el
+    // "2" con la primera y solo aparece en otra pasada
+    loop {
+        let unido = RE_SEPARADOR_EXPEDIENTE.replace_all(&texto, "$1$2$3");
+        if unido == texto {
+            break;
+        }
+        texto = unido.to_string();
+    }
+
+    let mut expediente: Option<String> = None;
+    let mut patron_usado = 0;
+
+    let patrones = RE_EXPEDIENTES.iter().chain(&config.patrones_expediente);
+    for (i, patron) in patrones.enumerate() {
+        if let Some(m) = patron.find(&texto) {
+            expediente = Some(m.as_str().to_uppercase().replace(' ', ""));
+            patron_usado = i;
+            break;
+        }
+    }
+
+    // Segunda pasada, más permisiva, cuando fallan los patrones estrictos
+    let mut relajado = false;
+    if expediente.is_none() && config.expediente_relajado {
+        if let Some(m) = RE_EXPEDIENTE_RELAJADO.find(&texto) {
+            expediente = Some(m.as_str().to_string());
+            patron_usado = usize::MAX;
+            relajado = true;
+        }
+    }
+
+    let mut expediente = match expediente {
+        Some(e) => e,
+        None => {
+            return Expediente {
+                numero: (p + 1).to_string(),
+                año: " ".to_string(),
+                encontrado: false,
+                año_dudoso: false,
+                año_fuera_de_rango: false,
+                relajado: false,
+                patron: None,
+            }
+        }
+    };
+
+    // Para el caso de EXP #### y no EXP-####
+    if patron_usado == 2 {
+        expediente = expediente.replace("EXP", "");
+    }
+
+    // Acortar si es muy largo (usar chars para UTF-8 safety)
+    let chars: Vec<char> = expediente.chars().collect();
+    if chars.len() > 30 {
+        expediente = chars[..25].iter().collect();
+    }
+
+    // Buscar el último dígito
+    let chars: Vec<char> = expediente.chars().collect();
+    let mut ultimo_digito = chars.len() as i32 - 1;
+    while ultimo_digito >= 0 && !chars[ultimo_digito as usize].is_ascii_digit() {
+        ultimo_digito -= 1;
+    }
+    if ultimo_digito >= 0 {
+        expediente = chars[..=(ultimo_digito as usize)].iter().collect();
+    }
+
+    // Extraer año (usar chars para UTF-8 safety)
+    let mut año = " ".to_string();
+    let mut año_dudoso = false;
+    let mut año_fuera_de_rango = false;
+    let chars: Vec<char> = expediente.chars().collect();
+    if chars.len() >= 4 {
+        let año_str: String = chars[chars.len() - 4..].iter().collect();
+        let separado = chars.len() >= 5 && matches!(chars[chars.len() - 5], '/' | '-');
+        if let Ok(año_num) = año_str.parse::<i32>() {
+            if (config.año_min..=config.año_max).contains(&año_num) {
+                año = año_num.to_string();
+                if chars.len() >= 5 {
+                    año_dudoso = !separado;
+                    expediente = chars[..chars.len() - 5].iter().collect();
+                }
+            } else {
+                año_fuera_de_rango = separado;
+            }
+        }
+    }
+
+    // Limpiar expediente
+    expediente = expediente.replace("EXP.", "EXP-").replace("NRO.", "");
+
+    if expediente.ends_with('-') {
+        expediente.pop();
+    }
+
+    if expediente.matches("EXP-").count() > 1 {
+        expediente = expediente.replacen("EXP-", "", 1);
+    } else if !expediente.contains("EXP-") && !expediente.contains("EJF-") {
+        expediente = format!("EXP-{}", expediente);
+    }
+
+    Expediente {
+        numero: expediente,
+        año,
+        encontrado: true,
+        año_dudoso,
+        año_fuera_de_rango,
+        relajado,
+        patron: (!relajado).then_some(patron_usado),
+    }
+}
+
+/// Extrae el monto del texto.
+///
+/// Equivale a [`extraer_monto_y_moneda`] descartando la moneda.
+pub fn extraer_monto(texto: &str, p: usize) -> String {
+    extraer_monto_y_moneda(texto, p).0
+}
+
+/// Extrae el monto del texto y la moneda en que está expresado.
+///
+/// Busca primero un monto en pesos entre paréntesis, luego uno en dólares
+/// ("U$S 1.500,00", "USD 1500") y por último uno en letras ("PESOS DOS MIL").
+/// Si no encuentra ninguno devuelve el número de página y una moneda vacía.
+pub fn extraer_monto_y_moneda(texto: &str, p: usize) -> (String, String) {
+    let texto = juntar_parentesis_monto(texto);
+
+    // Buscar patrón ($...) sin lookbehind
+    if let Some(m) = RE_MONTO.captures(&texto).and_then(|c| c.get(1)) {
+        return (normalizar_monto(m.as_str()), MONEDA_PESOS.to_string());
+    }
+
+    if let Some(m) = RE_MONTO_DOLARES.captures(&texto).and_then(|c| c.get(1)) {
+        return (normalizar_monto(m.as_str()), MONEDA_DOLARES.to_string());
+    }
+
+    match extraer_monto_en_letras(&texto) {
+        Some(monto) => (monto, MONEDA_PESOS.to_string()),
+        None => ((p + 1).to_string(), String::new()),
+    }
+}
+
+/// Texto del monto `monto` tal como se capturó en la página ("($1.234,56)",
+/// "U$S 1.500"), para revisar la normalización sin volver al PDF.
+///
+/// Es la primera coincidencia de los patrones de [`extraer_monto_y_moneda`] cuyo
+/// valor normalizado es `monto`, con los espacios del paréntesis ya juntados.
+/// Vacío si no aparece, como con el número de página de respaldo.
+pub fn extraer_monto_original(texto: &str, monto: &str) -> String {
+    let texto = juntar_parentesis_monto(texto);
+    RE_MONTO
+        .captures_iter(&texto)
+        .chain(RE_MONTO_DOLARES.captures_iter(&texto))
+        .find(|c| {
+            c.get(1)
+                .is_some_and(|m| normalizar_monto(m.as_str()) == monto)
+        })
+        .map(|c| c[0].trim().to_string())
+        .or_else(|| {
+            let coincidencia = RE_MONTO_PALABRAS.find(&texto)?;
+            (extraer_monto_en_letras(&texto)? == monto)
+                .then(|| coincidencia.as_str().trim().to_string())
+        })
+        .unwrap_or_default()
+}
+
+/// Redondea el monto normalizado ("1234.565") a `decimales` decimales, con
+/// redondeo a par en los empates ("0.125" → "0.12", "0.135" → "0.14").
+///
+/// Se redondea sobre los dígitos del texto, no sobre un `f64`, para que montos
+/// como "2.675" no se desvíen por la representación binaria. Un monto con menos
+/// decimales se completa con ceros; el que no es un número se devuelve sin cambios.
+pub fn redondear_monto(monto: &str, decimales: usize) -> String {
+    let (negativo, valor) = match monto.strip_prefix('-') {
+        Some(resto) => (true, resto),
+        None => (false, monto),
+    };
+    let (entero, fraccion) = valor.split_once('.').unwrap_or((valor, ""));
+    let es_numero = |digitos: &str| digitos.bytes().all(|b| b.is_ascii_digit());
+    if entero.is_empty() || !es_numero(entero) || !es_numero(fraccion) {
+        return monto.to_string();
+    }
+
+    let mut digitos: Vec<u8> = entero.bytes().chain(fraccion.bytes()).collect();
+    digitos.resize(entero.len() + fraccion.len().max(decimales), b'0');
+    let resto = digitos.split_off(entero.len() + decimales);
+    let subir = match resto.first() {
+        Some(b'6'..=b'9') => true,
+        Some(b'5') => {
+            resto[1..].iter().any(|&d| d != b'0')
+                || digitos.last().is_some_and(|&d| (d - b'0') % 2 == 1)
+        }
+        _ => false,
+    };
+    if subir {
+        let mut acarreo = true;
+        for d in digitos.iter_mut().rev() {
+            if *d == b'9' {
+                *d = b'0';
+            } else {
+                *d += 1;
+                acarreo = false;
+                break;
+            }
+        }
+        if acarreo {
+            digitos.insert(0, b'1');
+        }
+    }
+
+    let corte = digitos.len() - decimales;
+    let entero = String::from_utf8_lossy(&digitos[..corte]);
+    let fraccion = String::from_utf8_lossy(&digitos[corte..]);
+    let signo = if negativo && digitos.iter().any(|&d| d != b'0') {
+        "-"
+    } else {
+        ""
+    };
+    if decimales == 0 {
+        format!("{}{}", signo, entero)
+    } else {
+        format!("{}{}.{}", signo, entero, fraccion)
+    }
+}
+
+/// Montos en pesos entre paréntesis ("($...)") distintos, normalizados y en el
+/// orden en que aparecen en el texto.
+///
+/// El primero es el que toma [`extraer_monto_y_moneda`]; si hay más de uno,
+/// [`EstrategiaMonto`](crate::EstrategiaMonto) decide cuál usar.
+pub fn extraer_montos(texto: &str) -> Vec<String> {
+    let texto = juntar_parentesis_monto(texto);
+    let mut vistos = HashSet::new();
+    RE_MONTO
+        .captures_iter(&texto)
+        .filter_map(|c| c.get(1).map(|m| normalizar_monto(m.as_str())))
+        .filter(|monto| vistos.insert(monto.clone()))
+        .collect()
+}
+
+/// Cantidad de montos distintos, en pesos o en dólares, que aparecen en el texto.
+///
+/// Más de uno indica que el monto elegido por [`extraer_monto_y_moneda`] puede no ser el correcto.
+pub fn contar_montos(texto: &str) -> usize {
+    let texto = juntar_parentesis_monto(texto);
+    let montos: HashSet<String> = RE_MONTO
+        .captures_iter(&texto)
+        .chain(RE_MONTO_DOLARES.captures_iter(&texto))
+        .filter_map(|c| c.get(1).map(|m| normalizar_monto(m.as_str())))
+        .collect();
+    montos.len()
+}
+
+/// Quita los espacios alrededor del "$" del paréntesis del monto ("(  $ 1.500)" → "($1.500)"),
+/// para que [`PATRON_MONTO`] lo encuentre sin importar cómo se espació. Un signo
+/// menos delante del "$" pasa detrás ("(-$1.500)" → "($-1.500)").
+fn juntar_parentesis_monto(texto: &str) -> Cow<'_, str> {
+    RE_ESPACIOS_MONTO.replace_all(texto, "($$${1}")
+}
+
+/// Normaliza los separadores de un monto capturado a punto decimal y sin miles.
+///
+/// Los créditos y reversiones ("$-1.500,00", "$1.500,00 CR") conservan el signo:
+/// "-1500.00".
+pub(crate) fn normalizar_monto(coincidencia: &str) -> String {
+    let mut monto = coincidencia.replace(['$', ' '], "");
+
+    let mut negativo = false;
+    if let Some(resto) = monto.strip_prefix(['-', '−']) {
+        negativo = true;
+        monto = resto.to_string();
+    }
+    let bytes = monto.as_bytes();
+    if bytes.len() > 2 && bytes[bytes.len() - 2..].eq_ignore_ascii_case(b"CR") {
+        negativo = true;
+        monto.truncate(monto.len() - 2);
+    }
+
+    // Limpiar terminaciones
+    if monto.ends_with(".-") {
+        monto = monto[..monto.len() - 2].to_string();
+    } else if monto.ends_with('.') {
+        monto = monto[..monto.len() - 1].to_string();
+    }
+
+    let decimal = separador_decimal(&monto);
+    let signo = if negativo { "-" } else { "" };
+    let digitos: String = monto
+        .char_indices()
+        .filter_map(|(i, c)| match c {
+            '.' | ',' if Some(i) == decimal => Some('.'),
+            '.' | ',' => None,
+            c => Some(c),
+        })
+        .collect();
+    format!("{}{}", signo, digitos)
+}
+
+/// Signos de puntuación habituales en las órdenes, que no cuentan como símbolos extraños.
+const PUNTUACION_HABITUAL: &str = ".,;:()$/-°º\"'";
+
+/// Proporción de símbolos (ver [`proporcion_simbolos`]) a partir de la cual el
+/// texto extraído se considera mal decodificado.
+pub(crate) const PROPORCION_MAX_SIMBOLOS: f64 = 0.25;
+
+/// Proporción de caracteres del texto, sin contar espacios, que no son letras,
+/// dígitos ni la puntuación habitual.
+///
+/// Una fuente con codificación propia produce texto lleno de símbolos que no
+/// coincide con ningún patrón; una proporción alta indica que conviene usar OCR.
+pub(crate) fn proporcion_simbolos(texto: &str) -> f64 {
+    let (mut total, mut simbolos) = (0, 0);
+    for c in texto.chars().filter(|c| !c.is_whitespace()) {
+        total += 1;
+        if !c.is_alphanumeric() && !PUNTUACION_HABITUAL.contains(c) {
+            simbolos += 1;
+        }
+    }
+    if total == 0 {
+        return 0.0;
+    }
+    simbolos as f64 / total as f64
+}
+
+/// Decide cuál de los separadores de un monto es el decimal y devuelve su posición,
+/// o `None` si el monto es un entero.
+///
+/// Reglas, en orden:
+/// - Si aparecen punto y coma, el último en aparecer es el decimal: `1.234,56`, `1,234.56`.
+/// - Un único separador seguido de exactamente tres dígitos es de miles: `1.500`;
+///   con uno o dos dígitos detrás es decimal: `1500,5`, `1500.50`.
+/// - Un separador repetido es de miles, salvo que el último grupo tenga dos dígitos:
+///   `1.234.567` y `2,500,000` son enteros, `1.234.567.89` tiene decimales.
+fn separador_decimal(monto: &str) -> Option<usize> {
+    let separadores: Vec<(usize, char)> = monto
+        .char_indices()
+        .filter(|&(_, c)| c == '.' || c == ',')
+        .collect();
+    let &(posicion, ultimo) = separadores.last()?;
+    if separadores.iter().any(|&(_, c)| c != ultimo) {
+        return Some(posicion);
+    }
+
+    let digitos_finales = monto[posicion + 1..]
+        .chars()
+        .filter(|c| c.is_ascii_digit())
+        .count();
+    let es_decimal = if separadores.len() == 1 {
+        digitos_finales > 0 && digitos_finales != 3
+    } else {
+        digitos_finales == 2
+    };
+    es_decimal.then_some(posicion)
+}
+
+/// Valor de una palabra numérica menor que mil, sin tildes y en minúsculas.
+fn valor_palabra(palabra: &str) -> Option<u64> {
+    let valor = match palabra {
+        "cero" => 0,
+        "un" | "uno" | "una" => 1,
+        "dos" => 2,
+        "tres" => 3,
+        "cuatro" => 4,
+        "cinco" => 5,
+        "seis" => 6,
+        "siete" => 7,
+        "ocho" => 8,
+        "nueve" => 9,
+        "diez" => 10,
+        "once" => 11,
+        "doce" => 12,
+        "trece" => 13,
+        "catorce" => 14,
+        "quince" => 15,
+        "dieciseis" => 16,
+        "diecisiete" => 17,
+        "dieciocho" => 18,
+        "diecinueve" => 19,
+        "veinte" => 20,
+        "veintiun" | "veintiuno" | "veintiuna" => 21,
+        "veintidos" => 22,
+        "veintitres" => 23,
+        "veinticuatro" => 24,
+        "veinticinco" => 25,
+        "veintiseis" => 26,
+        "veintisiete" => 27,
+        "veintiocho" => 28,
+        "veintinueve" => 29,
+        "treinta" => 30,
+        "cuarenta" => 40,
+        "cincuenta" => 50,
+        "sesenta" => 60,
+        "setenta" => 70,
+        "ochenta" => 80,
+        "noventa" => 90,
+        "cien" | "ciento" => 100,
+        "doscientos" | "doscientas" => 200,
+        "trescientos" | "trescientas" => 300,
+        "cuatrocientos" | "cuatrocientas" => 400,
+        "quinientos" | "quinientas" => 500,
+        "seiscientos" | "seiscientas" => 600,
+        "setecientos" | "setecientas" => 700,
+        "ochocientos" | "ochocientas" => 800,
+        "novecientos" | "novecientas" => 900,
+        _ => return None,
+    };
+    Some(valor)
+}
+
+/// Convierte un número escrito en palabras ("dos millones trescientos mil") a su valor.
+///
+/// Se detiene en la primera palabra que no forma parte del número y devuelve
+/// `None` si no reconoció ninguna.
+pub fn palabras_a_numero(texto: &str) -> Option<u64> {
+    let mut total: u64 = 0;
+    let mut actual: u64 = 0;
+    let mut reconocidas = 0;
+
+    for palabra in texto.split_whitespace() {
+        let palabra: String = palabra
+            .to_lowercase()
+            .chars()
+            .map(|c| match c {
+                'á' => 'a',
+                'é' => 'e',
+                'í' => 'i',
+                'ó' => 'o',
+                'ú' => 'u',
+                _ => c,
+            })
+            .collect();
+
+        match palabra.as_str() {
+            "y" if reconocidas > 0 => continue,
+            "mil" => total += actual.max(1) * 1_000,
+            // "un millón" o "millones" solo: un millón; "dos mil millones": 2000 millones
+            "millon" | "millones" => total = (total + actual).max(1) * 1_000_000,
+            _ => match valor_palabra(&palabra) {
+                Some(valor) => {
+                    actual += valor;
+                    reconocidas += 1;
+                    continue;
+                }
+                None => break,
+            },
+        }
+        actual = 0;
+        reconocidas += 1;
+    }
+
+    (reconocidas > 0).then_some(total + actual)
+}
+
+/// Busca un monto escrito en letras después de "PESOS" y lo devuelve con punto decimal.
+fn extraer_monto_en_letras(texto: &str) -> Option<String> {
+    let caps = RE_MONTO_PALABRAS.captures(texto)?;
+    let entero = palabras_a_numero(caps.get(1)?.as_str())?;
+
+    match caps.get(2) {
+        Some(centavos) => Some(format!("{}.{:0>2}", entero, centavos.as_str())),
+        None => Some(entero.to_string()),
+    }
+}
+
+/// Extrae la designación del juzgado ("Juzgado de Primera Instancia Nro. 5").
+///
+/// Este campo no participa de las fórmulas de control, así que si no se
+/// encuentra devuelve una cadena vacía en lugar del número de página.
+pub fn extraer_juzgado(texto: &str) -> String {
+    let texto = normalizar_nro(texto);
+    RE_JUZGADO
+        .find(&texto)
+        .or_else(|| RE_JUZGADO_SIN_NUMERO.find(&texto))
+        .map(|m| m.as_str().split_whitespace().collect::<Vec<_>>().join(" "))
+        .unwrap_or_default()
+}
+
+/// Extrae la primera fecha en formato largo ("Buenos Aires, 15 de marzo de 2024").
+///
+/// Devuelve `None` si no hay ninguna o si la fecha no existe (por ejemplo, 31 de abril).
+pub fn extraer_fecha(texto: &str) -> Option<NaiveDate> {
+    RE_FECHA.captures_iter(texto).find_map(|caps| {
+        let dia: u32 = caps[1].parse().ok()?;
+        let mes = match caps[2].to_lowercase().as_str() {
+            "enero" => 1,
+            "febrero" => 2,
+            "marzo" => 3,
+            "abril" => 4,
+            "mayo" => 5,
+            "junio" => 6,
+            "julio" => 7,
+            "agosto" => 8,
+            "septiembre" | "setiembre" => 9,
+            "octubre" => 10,
+            "noviembre" => 11,
+            _ => 12,
+        };
+        let año: i32 = caps[3].parse().ok()?;
+        NaiveDate::from_ymd_opt(año, mes, dia)
+    })
+}
+
+/// Extrae el número de cheque del texto.
+///
+/// Equivale al primero de [`extraer_numeros_cheque`]; si no hay ninguno devuelve
+/// el número de página.
+pub fn extraer_numero_cheque(texto: &str, p: usize, config: &Config) -> String {
+    extraer_numeros_cheque(texto, config)
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| (p + 1).to_string())
+}
+
+/// Extrae todos los cheques, transferencias ITB y números INTERNO del texto, sin repetir.
+///
+/// Primero van los cheques, luego las ITB y por último los INTERNO, cada grupo en
+/// el orden en que aparece. El número de un cheque se recorta a
+/// `config.longitud_cheque` dígitos; si es más corto se toma tal cual. El prefijo
+/// del número INTERNO lo decide la primera de `config.reglas_prefijo` cuya palabra
+/// clave aparece en el texto; a ese número se le quitan los últimos cuatro dígitos
+/// y se descarta si quedan menos de dos o si es cero.
+pub fn extraer_numeros_cheque(texto: &str, config: &Config) -> Vec<String> {
+    let texto_limpio = normalizar_nro(texto).replace(['.', '-', ' '], "");
+    let mut numeros = Vec::new();
+
+    // Buscar ChequeNro
+    for patron in RE_CHEQUES.iter() {
+        for caps in patron.captures_iter(&texto_limpio) {
+            let numero_str = &caps[1];
+            let numero_str = &numero_str[..numero_str.len().min(config.longitud_cheque)];
+            if let Ok(numero) = numero_str.parse::<u64>() {
+                numeros.push(format!("CH {}", numero));
+            }
+        }
+    }
+
+    // Buscar ITBNro:
+    for caps in RE_ITB.captures_iter(&texto_limpio) {
+        if let Ok(numero) = caps[1].parse::<u64>() {
+            numeros.push(format!("ITB {}", numero));
+        }
+    }
+
+    // Buscar INTERNO:
+    let prefijo = config
+        .reglas_prefijo
+        .iter()
+        .find(|regla| texto.contains(&regla.palabra_clave))
+        .map_or(config.prefijo_interno.as_str(), |regla| &regla.prefijo);
+    for caps in RE_INTERNO.captures_iter(&texto_limpio) {
+        // El patrón solo captura dígitos ASCII, así que cortar por bytes es seguro
+        let numero_str = &caps[1];
+        let Some(largo) = numero_str.len().checked_sub(SUFIJO_INTERNO) else {
+            continue;
+        };
+        if largo < MIN_DIGITOS_INTERNO {
+            continue;
+        }
+        match numero_str[..largo].parse::<u64>() {
+            Ok(numero) if numero > 0 => numeros.push(format!("{} {}", prefijo, numero)),
+            _ => {}
+        }
+    }
+
+    let mut vistos = HashSet::new();
+    numeros.retain(|numero| vistos.insert(numero.clone()));
+    numeros
+}
+
+/// Controla que un número de cheque extraído sea verosímil.
+///
+/// Los cheques no tienen un dígito verificador público, así que solo se rechazan
+/// los casos groseros: el número cero (de cualquier tipo) y los cheques menores
+/// que [`MIN_NUMERO_CHEQUE`]. Devuelve el aviso para las observaciones
+/// ("Aviso: cheque inválido (CH 0, es cero)"), o `None` si el número parece
+/// válido o no es un cheque extraído.
+pub fn validar_cheque(cheque: &str) -> Option<String> {
+    let (tipo, numero) = cheque.split_once(' ')?;
+    let numero: u64 = numero.parse().ok()?;
+    if numero == 0 {
+        Some(crate::aviso(
+            "cheque inválido",
+            &format!("{}, es cero", cheque),
+        ))
+    } else if tipo == "CH" && numero < MIN_NUMERO_CHEQUE {
+        Some(crate::aviso(
+            "cheque sospechoso",
+            &format!("{}, tiene muy pocos dígitos", cheque),
+        ))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Aplica `extraer_monto` a un monto entre paréntesis rodeado de texto.
+    fn monto(valor: &str) -> String {
+        extraer_monto(&format!("por la suma de PESOS ({}) a favor", valor), 0)
+    }
+
+    #[test]
+    fn monto_miles_con_punto_y_decimales_con_coma() {
+        assert_eq!(monto("$1.234.567,89"), "1234567.89");
+    }
+
+    #[test]
+    fn monto_miles_con_coma_y_decimales_con_punto() {
+        assert_eq!(monto("$1,234.56"), "1234.56");
+    }
+
+    #[test]
+    fn monto_solo_puntos_con_decimales() {
+        assert_eq!(monto("$1.234.567.89"), "1234567.89");
+    }
+
+    #[test]
+    fn monto_solo_comas_con_decimales() {
+        assert_eq!(monto("$1,234,567,89"), "1234567.89");
+    }
+
+    #[test]
+    fn monto_entero_con_miles() {
+        assert_eq!(monto("$2.000"), "2000");
+    }
+
+    #[test]
+    fn monto_terminado_en_punto_guion() {
+        assert_eq!(monto("$2.000.-"), "2000");
+    }
+
+    #[test]
+    fn monto_terminado_en_punto() {
+        assert_eq!(monto("$2.000,50."), "2000.50");
+    }
+
+    #[test]
+    fn monto_con_espacio_despues_del_parentesis() {
+        assert_eq!(
+            extraer_monto("la suma de ( $1.500,00) a favor", 0),
+            "1500.00"
+        );
+    }
+
+    #[test]
+    fn monto_con_dos_espacios_despues_del_parentesis() {
+        assert_eq!(
+            extraer_monto("la suma de (  $1.500,00) a favor", 0),
+            "1500.00"
+        );
+    }
+
+    #[test]
+    fn monto_con_espacio_despues_del_signo() {
+        assert_eq!(
+            extraer_monto("la suma de ($ 1.500,00) a favor", 0),
+            "1500.00"
+        );
+    }
+
+    #[test]
+    fn monto_con_espacios_a_ambos_lados_del_signo() {
+        assert_eq!(
+            extraer_monto("la suma de (\t$  1.500,00) a favor", 0),
+            "1500.00"
+        );
+    }
+
+    #[test]
+    fn monto_miles_con_punto_sin_decimales() {
+        assert_eq!(monto("$1.500"), "1500");
+    }
+
+    #[test]
+    fn monto_un_decimal_con_coma() {
+        assert_eq!(monto("$1500,5"), "1500.5");
+    }
+
+    #[test]
+    fn monto_sin_miles_con_decimales_con_punto() {
+        assert_eq!(monto("$1500.50"), "1500.50");
+    }
+
+    #[test]
+    fn monto_miles_con_coma_sin_decimales() {
+        assert_eq!(monto("$2,500"), "2500");
+    }
+
+    #[test]
+    fn monto_entero_con_miles_con_coma() {
+        assert_eq!(monto("$2,500,000"), "2500000");
+    }
+
+    #[test]
+    fn monto_en_dolares_entero_con_miles_con_coma() {
+        assert_eq!(
+            extraer_monto_y_moneda("un cheque por U$S 2,500,000 a favor", 0),
+            ("2500000".to_string(), MONEDA_DOLARES.to_string())
+        );
+    }
+
+    #[test]
+    fn monto_negativo_conserva_el_signo() {
+        assert_eq!(monto("$-1.500,00"), "-1500.00");
+    }
+
+    #[test]
+    fn monto_con_signo_antes_del_peso() {
+        assert_eq!(monto("-$1.500,00"), "-1500.00");
+    }
+
+    #[test]
+    fn monto_con_sufijo_cr_es_negativo() {
+        assert_eq!(monto("$1.500,00 CR"), "-1500.00");
+    }
+
+    #[test]
+    fn texto_normal_casi_no_tiene_simbolos() {
+        let texto = "Expediente N° 1234/2020, por la suma de ($1.500,00) a favor de \"PEREZ\"";
+        assert_eq!(proporcion_simbolos(texto), 0.0);
+    }
+
+    #[test]
+    fn texto_mal_codificado_tiene_muchos_simbolos() {
+        assert!(proporcion_simbolos("#%&! <>*+ \u{fffd}\u{e001}") > 0.9);
+        assert!(proporcion_simbolos("Ex#%&!>e<*+") > PROPORCION_MAX_SIMBOLOS);
+    }
+
+    #[test]
+    fn monto_corto_sin_separadores() {
+        assert_eq!(monto("$50"), "50");
+    }
+
+    #[test]
+    fn monto_ausente_devuelve_numero_de_pagina() {
+        assert_eq!(extraer_monto("sin importe en esta página", 4), "5");
+    }
+
+    /// Aplica `extraer_expediente_y_año` con la configuración por defecto.
+    fn expediente(texto: &str) -> (String, String) {
+        extraer_expediente_y_año(texto, 0, &Config::default())
+    }
+
+    fn par(expediente: &str, año: &str) -> (String, String) {
+        (expediente.to_string(), año.to_string())
+    }
+
+    #[test]
+    fn expediente_con_palabra_completa_y_barra() {
+        assert_eq!(
+            expediente("Expediente 12345/2020, sobre cobro de pesos"),
+            par("EXP-12345", "2020")
+        );
+    }
+
+    #[test]
+    fn año_fuera_de_rango_se_marca_y_queda_en_el_numero() {
+        let fuera = extraer_expediente("Expediente 12345/1985, sobre", 0, &Config::default());
+        assert!(fuera.año_fuera_de_rango);
+        assert_eq!(fuera.numero, "EXP-12345/1985");
+        let dentro = extraer_expediente("Expediente 12345/2020, sobre", 0, &Config::default());
+        assert!(!dentro.año_fuera_de_rango);
+    }
+
+    #[test]
+    fn expediente_abreviado_expte() {
+        assert_eq!(
+            expediente("en autos Expte. 4567/2019, caratulados"),
+            par("EXP-4567", "2019")
+        );
+    }
+
+    #[test]
+    fn expediente_abreviado_con_n_y_grado() {
+        assert_eq!(
+            expediente("en autos Expte. N° 1234/2020, caratulados"),
+            par("EXP-1234", "2020")
+        );
+    }
+
+    #[test]
+    fn expediente_abreviado_con_nro() {
+        assert_eq!(
+            expediente("en autos Expte. Nro. 1234/2020, caratulados"),
+            par("EXP-1234", "2020")
+        );
+    }
+
+    #[test]
+    fn expediente_con_palabra_completa_y_n_con_grado() {
+        assert_eq!(
+            expediente("Expediente Nº 12345/2020, sobre cobro de pesos"),
+            par("EXP-12345", "2020")
+        );
+    }
+
+    #[test]
+    fn expediente_con_punto_sin_coma_usa_numero_y_año() {
+        assert_eq!(
+            expediente("EXP. 9999-2018 sin coma"),
+            par("EXP-9999", "2018")
+        );
+    }
+
+    #[test]
+    fn expediente_con_espacio_en_lugar_de_guion() {
+        assert_eq!(
+            expediente("según EXP 5555/2021, del registro"),
+            par("EXP-5555", "2021")
+        );
+    }
+
+    #[test]
+    fn expediente_con_espacios_alrededor_de_los_separadores() {
+        assert_eq!(
+            expediente("según EXP - 1234 / 2020, del registro"),
+            par("EXP-1234", "2020")
+        );
+    }
+
+    #[test]
+    fn expediente_con_espacios_en_separadores_seguidos() {
+        assert_eq!(
+            expediente("según EXP 12 - 34 - 2020, del registro"),
+            par("EXP-12-34", "2020")
+        );
+        assert_eq!(
+            expediente("según EXP 1 - 2 - 2020, del registro"),
+            par("EXP-1-2", "2020")
+        );
+    }
+
+    #[test]
+    fn expediente_sin_prefijo_con_espacios_alrededor_de_la_barra() {
+        assert_eq!(
+            expediente("causa 12345 / 2020 en trámite"),
+            par("EXP-12345", "2020")
+        );
+    }
+
+    #[test]
+    fn expediente_con_varios_espacios_y_saltos_de_linea() {
+        assert_eq!(
+            expediente("Expediente\n  12345 -\t2020, sobre cobro"),
+            par("EXP-12345", "2020")
+        );
+    }
+
+    #[test]
+    fn expediente_ejf_conserva_su_prefijo() {
+        assert_eq!(
+            expediente("causa EJF-777/2022, en trámite"),
+            par("EJF-777", "2022")
+        );
+    }
+
+    #[test]
+    fn expediente_con_año_fuera_de_rango_no_separa_el_año() {
+        assert_eq!(
+            expediente("EXP-1234/1985, archivado"),
+            par("EXP-1234/1985", " ")
+        );
+    }
+
+    #[test]
+    fn expediente_respeta_el_rango_de_años_configurado() {
+        let config = Config {
+            año_min: 1980,
+            ..Config::default()
+        };
+        assert_eq!(
+            extraer_expediente_y_año("EXP-1234/1985, archivado", 0, &config),
+            par("EXP-1234", "1985")
+        );
+    }
+
+    #[test]
+    fn expediente_ausente_devuelve_numero_de_pagina() {
+        assert_eq!(
+            extraer_expediente_y_año("texto sin datos de la causa", 2, &Config::default()),
+            par("3", " ")
+        );
+    }
+
+    #[test]
+    fn expediente_relajado_solo_si_se_pide() {
+        let texto = "causa 4567/19 en tramite";
+        assert_eq!(expediente(texto), par("1", " "));
+
+        let config = Config {
+            expediente_relajado: true,
+            ..Config::default()
+        };
+        let resultado = extraer_expediente(texto, 0, &config);
+        assert_eq!(resultado.numero, "EXP-4567/19");
+        assert!(resultado.relajado);
+    }
+
+    #[test]
+    fn expediente_informa_el_patron_usado() {
+        let config = Config::default();
+        assert_eq!(
+            extraer_expediente("EXP 1234/2020, autos", 0, &config).patron,
+            Some(2)
+        );
+        assert_eq!(
+            extraer_expediente("causa 12345/2020 en trámite", 0, &config).patron,
+            Some(4)
+        );
+        assert_eq!(extraer_expediente("sin datos", 0, &config).patron, None);
+    }
+
+    /// Aplica `extraer_numero_cheque` a un número INTERNO de una transferencia MEP.
+    fn interno(numero: &str) -> String {
+        let texto = format!("Transferencia M.E.P. INTERNO: {} a favor", numero);
+        extraer_numero_cheque(&texto, 0, &Config::default())
+    }
+
+    #[test]
+    fn interno_quita_los_ultimos_cuatro_digitos() {
+        assert_eq!(interno("1234562024"), "MEP 123456");
+    }
+
+    #[test]
+    fn interno_de_seis_digitos_deja_dos() {
+        assert_eq!(interno("122024"), "MEP 12");
+    }
+
+    #[test]
+    fn interno_de_cinco_digitos_se_descarta() {
+        assert_eq!(interno("12024"), "1");
+    }
+
+    #[test]
+    fn interno_de_cuatro_digitos_se_descarta() {
+        assert_eq!(interno("2024"), "1");
+    }
+
+    #[test]
+    fn interno_cero_se_descarta() {
+        assert_eq!(interno("002024"), "1");
+    }
+
+    #[test]
+    fn interno_sin_palabra_clave_usa_el_prefijo_por_defecto() {
+        assert_eq!(
+            extraer_numero_cheque("INTERNO: 1234562024", 0, &Config::default()),
+            "ITB 123456"
+        );
+    }
+
+    #[test]
+    fn varios_montos_en_orden_y_sin_repetir() {
+        let texto = "según ($ 500,00) y luego ($1.500,00) y otra vez ($500,00)";
+        assert_eq!(extraer_montos(texto), vec!["500.00", "1500.00"]);
+    }
+
+    #[test]
+    fn monto_original_es_el_texto_del_monto_elegido() {
+        let texto = "citado ($9.000,00) y pago ($12.000,00)";
+        assert_eq!(extraer_monto_original(texto, "12000.00"), "($12.000,00)");
+        assert_eq!(extraer_monto_original("sin montos", "3"), "");
+    }
+
+    #[test]
+    fn variantes_de_numero_se_llevan_a_nro() {
+        for variante in [
+            "Nº", "N°", "N.º", "N º", "NÂº", "NÂ°", "Nro", "Nro.", "nro.", "NRO",
+        ] {
+            assert_eq!(normalizar_nro(&format!("{} 1234", variante)), "Nro. 1234");
+        }
+        assert_eq!(normalizar_nro("ChequeNº123"), "ChequeNro.123");
+        assert_eq!(normalizar_nro("Monroe, 1º piso"), "Monroe, 1º piso");
+        assert_eq!(normalizar_nro("Nros. 12 y 13"), "Nro. 12 y 13");
+        assert_eq!(normalizar_nro("Cheque nro12345678"), "Cheque Nro.12345678");
+        // Las palabras que empiezan con "nro" no son el indicador de número
+        assert_eq!(normalizar_nro("NROSARIO 123"), "NROSARIO 123");
+    }
+
+    #[test]
+    fn expediente_sin_indicador_de_numero() {
+        for texto in [
+            "Expediente NÂº 12345/2020, sobre cobro",
+            "Expte. N.º 12345/2020, sobre cobro",
+            "EXP. nro. 12345/2020, sobre cobro",
+        ] {
+            assert_eq!(expediente(texto), par("EXP-12345", "2020"), "{}", texto);
+        }
+    }
+
+    #[test]
+    fn cheque_itb_y_juzgado_con_cualquier_indicador() {
+        let config = Config::default();
+        assert_eq!(
+            extraer_numeros_cheque("Cheque N.º 12345678 e ITB nro.: 4455", &config),
+            vec!["CH 12345678", "ITB 4455"]
+        );
+        assert_eq!(
+            extraer_juzgado("Juzgado de Primera Instancia Nº 5, Secretaría"),
+            "Juzgado de Primera Instancia Nro. 5"
+        );
+    }
+
+    #[test]
+    fn redondeo_a_par_sobre_los_digitos() {
+        assert_eq!(redondear_monto("0.125", 2), "0.12");
+        assert_eq!(redondear_monto("0.135", 2), "0.14");
+        assert_eq!(redondear_monto("2.675", 2), "2.68");
+        assert_eq!(redondear_monto("0.1251", 2), "0.13");
+        assert_eq!(redondear_monto("999.995", 2), "1000.00");
+        assert_eq!(redondear_monto("-1500.004", 2), "-1500.00");
+        assert_eq!(redondear_monto("-0.001", 2), "0.00");
+        assert_eq!(redondear_monto("2.5", 0), "2");
+    }
+
+    #[test]
+    fn redondeo_completa_decimales_y_respeta_lo_que_no_es_numero() {
+        assert_eq!(redondear_monto("1500", 2), "1500.00");
+        assert_eq!(redondear_monto("1500.5", 3), "1500.500");
+        assert_eq!(redondear_monto("1.500,00", 2), "1.500,00");
+        assert_eq!(redondear_monto("", 2), "");
+    }
+
+    #[test]
+    fn estrategia_mayor_elige_el_de_mayor_importe() {
+        let montos = extraer_montos("($9.000,00) citado, pago ($12.000,00) y ($50,00)");
+        let elegir = |estrategia: crate::EstrategiaMonto| estrategia.elegir(&montos).cloned();
+        assert_eq!(elegir(crate::EstrategiaMonto::Primero).unwrap(), "9000.00");
+        assert_eq!(elegir(crate::EstrategiaMonto::Mayor).unwrap(), "12000.00");
+        assert_eq!(elegir(crate::EstrategiaMonto::Ultimo).unwrap(), "50.00");
+    }
+
+    #[test]
+    fn cheque_de_ocho_digitos_es_valido() {
+        assert_eq!(validar_cheque("CH 12345678"), None);
+    }
+
+    #[test]
+    fn cheque_en_ceros_es_invalido() {
+        let cheque = extraer_numero_cheque("Cheque Nro 00000000", 0, &Config::default());
+        assert_eq!(cheque, "CH 0");
+        assert!(validar_cheque(&cheque).is_some());
+    }
+
+    #[test]
+    fn cheque_demasiado_chico_es_sospechoso() {
+        assert!(validar_cheque("CH 12").is_some());
+        // Las transferencias ITB no tienen un largo fijo
+        assert_eq!(validar_cheque("ITB 12"), None);
+    }
+
+    #[test]
+    fn numero_de_pagina_no_se_valida() {
+        assert_eq!(validar_cheque("3"), None);
+    }
+
+    #[test]
+    fn caratula_repetida_da_un_solo_registro() {
+        let config = Config {
+            todas_las_caratulas: true,
+            min_caracteres: 0,
+            ..Config::default()
+        };
+        let texto = r#"en autos "PEREZ JUAN s/ COBRO" por la suma de PESOS ($1.000,00)
+            Cheque Nro 12345678. Se reitera que en autos "PEREZ JUAN s/ COBRO" se libró
+            el cheque; en autos "GOMEZ ANA s/ COBRO" también."#;
+        assert_eq!(
+            extraer_textos_entre_comillas(texto, &config),
+            ["PEREZ JUAN s/ COBRO", "GOMEZ ANA s/ COBRO"]
+        );
+        let (_, datos) = crate::procesar_texto(texto, None, 0, 1, "a.pdf", &config);
+        let nombres: Vec<&str> = datos.iter().map(|d| d.nombre.as_str()).collect();
+        assert_eq!(nombres, ["PEREZ JUAN s/ COBRO", "GOMEZ ANA s/ COBRO"]);
+    }
+
+    #[test]
+    fn caratulas_de_una_pagina_no_son_cheques_duplicados() {
+        let config = Config {
+            todas_las_caratulas: true,
+            min_caracteres: 0,
+            ..Config::default()
+        };
+        let texto = r#"en autos "PEREZ JUAN s/ COBRO" y en autos "GOMEZ ANA s/ COBRO"
+            por la suma de PESOS ($1.000,00), Cheque Nro 12345678"#;
+        let (_, mut datos) = crate::procesar_texto(texto, None, 0, 1, "a.pdf", &config);
+        assert_eq!(datos.len(), 2);
+        assert!(crate::marcar_duplicados(&mut datos).is_empty());
+        let aviso = crate::aviso("cheque duplicado", "");
+        assert!(datos.iter().all(|d| !d.observaciones.contains(&aviso)));
+
+        // El mismo cheque en otra página sí es un duplicado, de los tres registros
+        datos.extend(crate::procesar_texto(texto, None, 1, 2, "a.pdf", &config).1);
+        let duplicados = crate::detectar_duplicados(&datos);
+        assert_eq!(duplicados.len(), 1);
+        assert_eq!(duplicados[0].indices.len(), 4);
+    }
+
+    #[test]
+    fn todas_las_advertencias_llevan_el_prefijo_de_aviso() {
+        let config = Config {
+            min_caracteres: 0,
+            validar_cheques: true,
+            ..Config::default()
+        };
+        let texto = r#"en autos "PEREZ JUAN s/ COBRO" y autos "GOMEZ ANA s/ COBRO",
+            por PESOS ($9.000,00) y PESOS ($12.000,00), Cheque Nro 12 y Cheque Nro 345"#;
+        let (_, datos) = crate::procesar_texto(texto, None, 0, 1, "a.pdf", &config);
+        let observaciones = &datos[0].observaciones;
+        assert!(observaciones.contains(&"Aviso: varios autos".to_string()));
+        assert!(observaciones
+            .contains(&"Aviso: varios montos (9000.00, 12000.00, se tomó el primero)".to_string()));
+        assert!(observaciones
+            .iter()
+            .all(|o| o.starts_with(crate::PREFIJO_AVISO)));
+    }
+
+    #[test]
+    fn palabras_miles() {
+        assert_eq!(palabras_a_numero("dos mil"), Some(2_000));
+        assert_eq!(palabras_a_numero("treinta y un mil"), Some(31_000));
+        assert_eq!(palabras_a_numero("mil"), Some(1_000));
+    }
+
+    #[test]
+    fn palabras_millones() {
+        assert_eq!(
+            palabras_a_numero("un millón quinientos mil"),
+            Some(1_500_000)
+        );
+        assert_eq!(palabras_a_numero("dos mil millones"), Some(2_000_000_000));
+    }
+
+    #[test]
+    fn palabras_con_acentos_y_mayusculas() {
+        assert_eq!(palabras_a_numero("DIECISÉIS MIL VEINTITRÉS"), Some(16_023));
+        assert_eq!(palabras_a_numero("no es un número"), None);
+    }
+
+    #[test]
+    fn monto_en_letras_con_centavos() {
+        assert_eq!(
+            extraer_monto_en_letras("la suma de PESOS DOS MIL QUINIENTOS CON 50/100 a favor"),
+            Some("2500.50".to_string())
+        );
+        assert_eq!(
+            extraer_monto_en_letras("PESOS CIEN CON 5/100."),
+            Some("100.05".to_string())
+        );
+    }
+
+    #[test]
+    fn rango_de_paginas_enorme_no_se_enumera() {
+        let seleccion = crate::parsear_paginas("1-4294967295").unwrap();
+        assert!(seleccion.contiene(1));
+        assert!(seleccion.contiene(u32::MAX));
+        assert_eq!(seleccion.fuera_de(10), ["11-4294967295"]);
+    }
+
+    #[test]
+    fn rangos_de_paginas_superpuestos_se_unen() {
+        let seleccion = crate::parsear_paginas("7,2-5,1-3,6").unwrap();
+        assert!((1..=7).all(|p| seleccion.contiene(p)));
+        assert!(!seleccion.contiene(8));
+        assert_eq!(seleccion.fuera_de(3), ["4-7"]);
+        assert!(crate::parsear_paginas("3-1").is_err());
+    }
+
+    #[test]
+    fn paginas_sin_cheque_no_son_duplicados() {
+        let config = Config {
+            min_caracteres: 0,
+            ..Config::default()
+        };
+        let texto = r#"en autos "PEREZ JUAN s/ COBRO" por la suma de PESOS ($1.000,00)"#;
+        let mut datos = Vec::new();
+        for archivo in ["a.pdf", "b.pdf"] {
+            datos.extend(crate::procesar_texto(texto, None, 2, 3, archivo, &config).1);
+        }
+        // Ambos toman el número de página como cheque de respaldo
+        assert_eq!(datos[0].cheque, "3");
+        assert_eq!(datos[1].cheque, "3");
+        assert!(crate::detectar_duplicados(&datos).is_empty());
+    }
+}
@@ -0,0 +1,247 @@
+//! Lectura de los campos de formulario (AcroForm) de los PDF rellenables.
+//!
+//! En los formularios digitales el monto y el cheque están en los campos y no
+//! en el texto de la página, así que `extract_text` no los ve. Los campos con
+//! nombres conocidos ("monto", "cheque", "expediente"...) reemplazan a lo que
+//! se extrajo del texto; los demás datos siguen saliendo de los patrones.
+
+use crate::extraccion::{normalizar_monto, MONEDA_DOLARES, MONEDA_PESOS};
+use crate::{
+    extraer_expediente, extraer_fecha, limpiar_nombre, redondear_monto, Confianza, Config,
+    DatosPagina,
+};
+use chrono::NaiveDate;
+use lopdf::{decode_text_string, Dictionary, Document, Object};
+use std::collections::BTreeMap;
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// Niveles de `/Parent` que se recorren buscando el nombre y el valor de un campo
+const MAX_NIVELES: usize = 32;
+
+/// Valores de los campos conocidos del formulario de una página.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Formulario {
+    nombre: Option<String>,
+    expediente: Option<String>,
+    año: Option<String>,
+    monto: Option<String>,
+    cheque: Option<String>,
+    juzgado: Option<String>,
+    fecha: Option<String>,
+}
+
+/// Campos de formulario de cada página (por número de página) que tiene alguno
+/// con nombre conocido; vacío si el documento no tiene formulario.
+pub(crate) fn leer(doc: &Document) -> BTreeMap<u32, Formulario> {
+    if !doc
+        .catalog()
+        .is_ok_and(|catalogo| catalogo.has(b"AcroForm"))
+    {
+        return BTreeMap::new();
+    }
+    doc.get_pages()
+        .into_iter()
+        .filter_map(|(page_num, page_id)| {
+            let mut formulario = Formulario::default();
+            for anotacion in doc.get_page_annotations(page_id).unwrap_or_default() {
+                let es_campo = anotacion
+                    .get(b"Subtype")
+                    .and_then(Object::as_name)
+                    .is_ok_and(|tipo| tipo == b"Widget");
+                if !es_campo {
+                    continue;
+                }
+                if let Some((nombre, valor)) = nombre_y_valor(doc, anotacion) {
+                    formulario.asignar(&nombre, valor);
+                }
+            }
+            (!formulario.vacio()).then_some((page_num, formulario))
+        })
+        .collect()
+}
+
+/// Nombre propio (`/T`) y valor (`/V`) del campo de un widget; ambos pueden
+/// estar en el widget o heredarse de los campos padres.
+fn nombre_y_valor(doc: &Document, widget: &Dictionary) -> Option<(String, String)> {
+    let (mut nombre, mut valor) = (None, None);
+    let mut nodo = Some(widget);
+    for _ in 0..MAX_NIVELES {
+        let Some(campo) = nodo else {
+            break;
+        };
+        if nombre.is_none() {
+            nombre = campo
+                .get(b"T")
+                .ok()
+                .and_then(|t| decode_text_string(t).ok());
+        }
+        if valor.is_none() {
+            valor = campo.get(b"V").ok().and_then(texto_valor);
+        }
+        nodo = campo
+            .get(b"Parent")
+            .and_then(Object::as_reference)
+            .and_then(|id| doc.get_dictionary(id))
+            .ok();
+    }
+    Some((nombre?, valor?))
+}
+
+/// Texto del valor de un campo: el texto escrito o la opción elegida.
+fn texto_valor(valor: &Object) -> Option<String> {
+    let texto = match valor {
+        Object::String(..) => decode_text_string(valor).ok()?,
+        Object::Name(nombre) => String::from_utf8_lossy(nombre).into_owned(),
+        _ => return None,
+    };
+    let texto = texto.trim();
+    // Una casilla sin marcar vale /Off
+    (!texto.is_empty() && texto != "Off").then(|| texto.to_string())
+}
+
+/// Nombre del campo en minúsculas, sin acentos ni separadores ("Nro. de Cheque" → "nrodecheque").
+fn clave(nombre: &str) -> String {
+    nombre
+        .nfd()
+        .filter(|c| !is_combining_mark(*c) && c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+impl Formulario {
+    /// Guarda el valor si el nombre del campo es uno de los conocidos.
+    fn asignar(&mut self, nombre: &str, valor: String) {
+        let destino = match clave(nombre).as_str() {
+            "nombre" | "autos" | "caratula" => &mut self.nombre,
+            "expediente" | "expte" | "nroexpediente" | "numeroexpediente" => &mut self.expediente,
+            "anio" | "ano" => &mut self.año,
+            "monto" | "importe" => &mut self.monto,
+            "cheque" | "nrocheque" | "nrodecheque" | "numerocheque" | "numerodecheque" => {
+                &mut self.cheque
+            }
+            "juzgado" => &mut self.juzgado,
+            "fecha" => &mut self.fecha,
+            _ => return,
+        };
+        destino.get_or_insert(valor);
+    }
+
+    fn vacio(&self) -> bool {
+        [
+            &self.nombre,
+            &self.expediente,
+            &self.año,
+            &self.monto,
+            &self.cheque,
+            &self.juzgado,
+            &self.fecha,
+        ]
+        .iter()
+        .all(|campo| campo.is_none())
+    }
+
+    /// Reemplaza en el registro los campos que trae el formulario y los anota en
+    /// las observaciones. Devuelve los nombres de los campos reemplazados, como
+    /// los de [`ConfianzaCampos::respaldos`](crate::ConfianzaCampos::respaldos).
+    ///
+    /// El expediente se normaliza como el del texto ("12345/2020" → "EXP-12345" y
+    /// el año); un monto o una fecha que no se pueden interpretar se ignoran.
+    pub(crate) fn aplicar(
+        &self,
+        dato: &mut DatosPagina,
+        p: usize,
+        config: &Config,
+    ) -> Vec<&'static str> {
+        let mut usados = Vec::new();
+
+        if let Some(nombre) = &self.nombre {
+            dato.nombre = limpiar_nombre(nombre, config);
+            dato.confianza.nombre = Confianza::Encontrado;
+            usados.push("nombre");
+        }
+
+        if let Some(valor) = &self.expediente {
+            let expediente = extraer_expediente(&format!("Expediente {}", valor), p, config);
+            if expediente.encontrado {
+                dato.expediente = expediente.numero;
+                if !expediente.año.trim().is_empty() {
+                    dato.año = expediente.año;
+                    dato.confianza.año = Confianza::Encontrado;
+                }
+            } else {
+                dato.expediente = valor.clone();
+            }
+            dato.confianza.expediente = Confianza::Encontrado;
+            dato.patron_expediente = None;
+            usados.push("expediente");
+        }
+
+        if let Some(año) = self.año.as_ref().filter(|a| a.parse::<i32>().is_ok()) {
+            dato.año = año.clone();
+            dato.confianza.año = Confianza::Encontrado;
+            usados.push("año");
+        }
+
+        if let Some(valor) = &self.monto {
+            let cifra: String = valor
+                .chars()
+                .filter(|c| c.is_ascii_digit() || matches!(c, '.' | ',' | '-'))
+                .collect();
+            let monto = normalizar_monto(&cifra);
+            if monto.parse::<f64>().is_ok() {
+                let mayusculas = valor.to_uppercase();
+                let dolares = ["U$S", "US$", "USD"].iter().any(|s| mayusculas.contains(s));
+                dato.monto = match config.decimales_monto {
+                    Some(decimales) => redondear_monto(&monto, decimales),
+                    None => monto,
+                };
+                dato.moneda = if dolares {
+                    MONEDA_DOLARES
+                } else {
+                    MONEDA_PESOS
+                }
+                .to_string();
+                dato.monto_original = valor.clone();
+                dato.confianza.monto = Confianza::Encontrado;
+                usados.push("monto");
+            }
+        }
+
+        if let Some(valor) = &self.cheque {
+            // Solo el número, como en el texto: "CH 12345678"
+            let solo_numero = valor
+                .chars()
+                .all(|c| c.is_ascii_digit() || " .-".contains(c));
+            let digitos: String = valor.chars().filter(char::is_ascii_digit).collect();
+            let digitos = &digitos[..digitos.len().min(config.longitud_cheque)];
+            dato.cheque = match digitos.parse::<u64>() {
+                Ok(numero) if solo_numero => format!("CH {}", numero),
+                _ => valor.clone(),
+            };
+            dato.confianza.cheque = Confianza::Encontrado;
+            usados.push("cheque");
+        }
+
+        if let Some(juzgado) = &self.juzgado {
+            dato.juzgado = juzgado.clone();
+            usados.push("juzgado");
+        }
+
+        let fecha = self.fecha.as_deref().and_then(|fecha| {
+            NaiveDate::parse_from_str(fecha, "%d/%m/%Y")
+                .or_else(|_| NaiveDate::parse_from_str(fecha, "%Y-%m-%d"))
+                .ok()
+                .or_else(|| extraer_fecha(fecha))
+        });
+        if fecha.is_some() {
+            dato.fecha = fecha;
+            usados.push("fecha");
+        }
+
+        if !usados.is_empty() {
+            dato.observaciones
+                .push(format!("Tomado del formulario: {}", usados.join(", ")));
+        }
+        usados
+    }
+}
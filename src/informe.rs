@@ -0,0 +1,352 @@
+//! Registro de lo ocurrido con cada página durante el procesamiento.
+
+use crate::{Confianza, DatosPagina, ProcError};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Resultado del procesamiento de una página o de un archivo completo.
+#[derive(Debug, Clone)]
+pub enum Evento {
+    /// Página de la que se extrajo un registro
+    Procesada {
+        archivo: String,
+        pagina: u32,
+        caracteres: usize,
+    },
+    /// Página salteada por tener poco texto
+    Omitida {
+        archivo: String,
+        pagina: u32,
+        caracteres: usize,
+    },
+    /// Página salteada por no tener monto "($...)" ni expediente (carátula, índice)
+    SinDatos {
+        archivo: String,
+        pagina: u32,
+        caracteres: usize,
+    },
+    /// Página cuyo texto no pudo extraerse
+    Fallida {
+        archivo: String,
+        pagina: u32,
+        mensaje: String,
+    },
+    /// Archivo de un lote que no pudo procesarse
+    ArchivoFallido { archivo: String, mensaje: String },
+    /// Advertencia sobre un archivo que se procesó igual, como un volcado de
+    /// texto que no se pudo guardar
+    Aviso { archivo: String, mensaje: String },
+}
+
+/// Evento de una página omitida o fallida tal como se escribe en el JSON de errores.
+#[derive(Serialize)]
+struct ErrorJson<'a> {
+    archivo: &'a str,
+    /// Página; `None` si falló el archivo completo
+    pagina: Option<u32>,
+    /// Motivo en forma de clave estable: "poco_texto", "sin_datos",
+    /// "error_pagina" o "error_archivo"
+    motivo: &'static str,
+    mensaje: String,
+}
+
+impl Evento {
+    /// Nombre del archivo al que corresponde el evento.
+    pub fn archivo(&self) -> &str {
+        match self {
+            Evento::Procesada { archivo, .. }
+            | Evento::Omitida { archivo, .. }
+            | Evento::SinDatos { archivo, .. }
+            | Evento::Fallida { archivo, .. }
+            | Evento::ArchivoFallido { archivo, .. }
+            | Evento::Aviso { archivo, .. } => archivo,
+        }
+    }
+}
+
+impl fmt::Display for Evento {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Evento::Procesada {
+                pagina, caracteres, ..
+            } => write!(
+                f,
+                "Procesando página {} ({} caracteres)",
+                pagina, caracteres
+            ),
+            Evento::Omitida {
+                pagina, caracteres, ..
+            } => write!(
+                f,
+                "Página {} omitida: solo {} caracteres",
+                pagina, caracteres
+            ),
+            Evento::SinDatos { pagina, .. } => {
+                write!(f, "Página {} omitida: no tiene monto ni expediente", pagina)
+            }
+            Evento::Fallida {
+                pagina, mensaje, ..
+            } => write!(
+                f,
+                "Error extrayendo texto de página {}: {}",
+                pagina, mensaje
+            ),
+            Evento::ArchivoFallido { archivo, mensaje } => {
+                write!(f, "Advertencia: se omite {}: {}", archivo, mensaje)
+            }
+            Evento::Aviso { mensaje, .. } => write!(f, "Advertencia: {}", mensaje),
+        }
+    }
+}
+
+/// Cuánto se muestra por pantalla durante el procesamiento.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Verbosidad {
+    /// Sin barra de progreso ni mensajes por página
+    Silencioso,
+    /// Barra de progreso, las páginas o archivos con error y las advertencias;
+    /// las omitidas se cuentan en el resumen
+    #[default]
+    Normal,
+    /// Barra de progreso y una línea por cada página, procesada u omitida
+    Detallado,
+}
+
+impl Verbosidad {
+    /// Si el evento se muestra por pantalla con esta verbosidad.
+    pub fn muestra(self, evento: &Evento) -> bool {
+        match self {
+            Verbosidad::Silencioso => false,
+            Verbosidad::Normal => {
+                matches!(
+                    evento,
+                    Evento::Fallida { .. } | Evento::ArchivoFallido { .. } | Evento::Aviso { .. }
+                )
+            }
+            Verbosidad::Detallado => true,
+        }
+    }
+}
+
+/// Eventos acumulados a lo largo de una corrida.
+#[derive(Debug, Clone, Default)]
+pub struct Informe {
+    pub eventos: Vec<Evento>,
+    pub verbosidad: Verbosidad,
+    /// Páginas de todos los documentos abiertos, incluidas las no seleccionadas
+    pub paginas_totales: usize,
+}
+
+/// Contadores de una corrida, para ver de un vistazo si el formato del documento
+/// está rompiendo los patrones.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Estadisticas {
+    pub paginas_totales: usize,
+    pub procesadas: usize,
+    pub omitidas_por_texto: usize,
+    /// Páginas salteadas por no tener monto ni expediente (`--omitir-sin-datos`)
+    pub omitidas_sin_datos: usize,
+    pub omitidas_por_error: usize,
+    /// Páginas que quedaron fuera de la selección de `--pages`
+    pub no_seleccionadas: usize,
+    pub archivos_fallidos: usize,
+    pub registros: usize,
+    /// Registros en que cada campo quedó con el valor de respaldo:
+    /// nombre, expediente, año, monto y cheque
+    pub respaldos: [usize; 5],
+    /// Registros cuyo expediente encontró cada patrón, por su posición (ver
+    /// [`Expediente::patron`](crate::Expediente::patron))
+    pub por_patron: BTreeMap<usize, usize>,
+}
+
+impl fmt::Display for Estadisticas {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Estadísticas:")?;
+        writeln!(f, "  Páginas totales:          {}", self.paginas_totales)?;
+        writeln!(f, "  Páginas procesadas:       {}", self.procesadas)?;
+        writeln!(f, "  Omitidas por poco texto:  {}", self.omitidas_por_texto)?;
+        if self.omitidas_sin_datos > 0 {
+            writeln!(f, "  Omitidas sin datos:       {}", self.omitidas_sin_datos)?;
+        }
+        writeln!(f, "  Omitidas por error:       {}", self.omitidas_por_error)?;
+        writeln!(f, "  No seleccionadas:         {}", self.no_seleccionadas)?;
+        if self.archivos_fallidos > 0 {
+            writeln!(f, "  Archivos con error:       {}", self.archivos_fallidos)?;
+        }
+        writeln!(f, "  Registros extraídos:      {}", self.registros)?;
+        let campos = ["nombre", "expediente", "año", "monto", "cheque"];
+        for (campo, respaldos) in campos.iter().zip(self.respaldos) {
+            writeln!(
+                f,
+                "  {:<11} {} sin encontrar de {}",
+                format!("{}:", campo),
+                respaldos,
+                self.registros
+            )?;
+        }
+        if !self.por_patron.is_empty() {
+            writeln!(f, "  Expedientes por patrón:")?;
+            for (patron, registros) in &self.por_patron {
+                writeln!(f, "    patrón {:<3} {}", patron + 1, registros)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Informe {
+    /// Crea un informe vacío que muestra los eventos según `verbosidad`.
+    pub fn new(verbosidad: Verbosidad) -> Self {
+        Informe {
+            eventos: Vec::new(),
+            verbosidad,
+            paginas_totales: 0,
+        }
+    }
+
+    /// Calcula las estadísticas de la corrida a partir de los eventos y de los
+    /// registros extraídos.
+    pub fn estadisticas(&self, datos: &[DatosPagina]) -> Estadisticas {
+        let mut respaldos = [0; 5];
+        let mut por_patron = BTreeMap::new();
+        for dato in datos {
+            if let Some(patron) = dato.patron_expediente {
+                *por_patron.entry(patron).or_default() += 1;
+            }
+            let c = &dato.confianza;
+            let campos = [c.nombre, c.expediente, c.año, c.monto, c.cheque];
+            for (cuenta, confianza) in respaldos.iter_mut().zip(campos) {
+                if confianza == Confianza::Respaldo {
+                    *cuenta += 1;
+                }
+            }
+        }
+
+        let procesadas = self.procesadas();
+        let omitidas_por_texto = self.contar(|e| matches!(e, Evento::Omitida { .. }));
+        let omitidas_sin_datos = self.contar(|e| matches!(e, Evento::SinDatos { .. }));
+        let omitidas_por_error = self.contar(|e| matches!(e, Evento::Fallida { .. }));
+        Estadisticas {
+            paginas_totales: self.paginas_totales,
+            procesadas,
+            omitidas_por_texto,
+            omitidas_sin_datos,
+            omitidas_por_error,
+            no_seleccionadas: self.paginas_totales.saturating_sub(
+                procesadas + omitidas_por_texto + omitidas_sin_datos + omitidas_por_error,
+            ),
+            archivos_fallidos: self.contar(|e| matches!(e, Evento::ArchivoFallido { .. })),
+            registros: datos.len(),
+            respaldos,
+            por_patron,
+        }
+    }
+
+    /// Registra un evento y lo muestra por pantalla si corresponde a la verbosidad.
+    pub fn registrar(&mut self, evento: Evento) {
+        if self.verbosidad.muestra(&evento) {
+            println!("{}", evento);
+        }
+        self.eventos.push(evento);
+    }
+
+    /// Registra un evento que ya se mostró al procesar la página, sin volver a mostrarlo.
+    pub(crate) fn registrar_mostrado(&mut self, evento: Evento) {
+        self.eventos.push(evento);
+    }
+
+    /// Cantidad de páginas procesadas.
+    pub fn procesadas(&self) -> usize {
+        self.contar(|e| matches!(e, Evento::Procesada { .. }))
+    }
+
+    /// Cantidad de páginas omitidas, por tener poco texto o por no tener datos.
+    pub fn omitidas(&self) -> usize {
+        self.contar(|e| matches!(e, Evento::Omitida { .. } | Evento::SinDatos { .. }))
+    }
+
+    /// Cantidad de páginas o archivos que fallaron.
+    pub fn fallidas(&self) -> usize {
+        self.contar(|e| matches!(e, Evento::Fallida { .. } | Evento::ArchivoFallido { .. }))
+    }
+
+    fn contar(&self, filtro: impl Fn(&Evento) -> bool) -> usize {
+        self.eventos.iter().filter(|e| filtro(e)).count()
+    }
+
+    /// Línea de resumen con los totales de la corrida.
+    pub fn resumen(&self) -> String {
+        format!(
+            "Resumen: {} procesadas, {} omitidas, {} con error",
+            self.procesadas(),
+            self.omitidas(),
+            self.fallidas()
+        )
+    }
+
+    /// Guarda las páginas omitidas y fallidas como un arreglo JSON de objetos con
+    /// `archivo`, `pagina`, `motivo` y `mensaje`, para que lo lean otros programas.
+    pub fn guardar_json(&self, path: &Path) -> Result<(), ProcError> {
+        self.escribir_json(path)
+            .map_err(|source| ProcError::Exportar {
+                path: path.to_path_buf(),
+                source,
+            })
+    }
+
+    fn escribir_json(&self, path: &Path) -> io::Result<()> {
+        let errores: Vec<ErrorJson> = self
+            .eventos
+            .iter()
+            .filter_map(|evento| {
+                let (pagina, motivo, mensaje) = match evento {
+                    Evento::Procesada { .. } | Evento::Aviso { .. } => return None,
+                    Evento::Omitida { pagina, .. } => {
+                        (Some(*pagina), "poco_texto", evento.to_string())
+                    }
+                    Evento::SinDatos { pagina, .. } => {
+                        (Some(*pagina), "sin_datos", evento.to_string())
+                    }
+                    Evento::Fallida {
+                        pagina, mensaje, ..
+                    } => (Some(*pagina), "error_pagina", mensaje.clone()),
+                    Evento::ArchivoFallido { mensaje, .. } => {
+                        (None, "error_archivo", mensaje.clone())
+                    }
+                };
+                Some(ErrorJson {
+                    archivo: evento.archivo(),
+                    pagina,
+                    motivo,
+                    mensaje,
+                })
+            })
+            .collect();
+        let mut archivo = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut archivo, &errores)?;
+        archivo.flush()
+    }
+
+    /// Guarda las páginas omitidas y fallidas en un archivo de texto, con el resumen al final.
+    pub fn guardar(&self, path: &Path) -> Result<(), ProcError> {
+        self.escribir(path).map_err(|source| ProcError::Exportar {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    fn escribir(&self, path: &Path) -> io::Result<()> {
+        let mut archivo = BufWriter::new(File::create(path)?);
+        for evento in &self.eventos {
+            if !matches!(evento, Evento::Procesada { .. }) {
+                writeln!(archivo, "{}: {}", evento.archivo(), evento)?;
+            }
+        }
+        writeln!(archivo, "{}", self.resumen())?;
+        archivo.flush()
+    }
+}
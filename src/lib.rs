@@ -0,0 +1,1049 @@
+//! PDF Procuración
+//!
+//! Biblioteca para procesar archivos PDF de procuración y extraer
+//! información relevante como: nombre, expediente, año, monto y número de
+//! cheque. Los datos pueden guardarse luego en un archivo Excel con formato.
+
+mod cache;
+pub mod config;
+pub mod error;
+pub mod excel;
+pub mod exportar;
+pub mod extraccion;
+mod formulario;
+pub mod informe;
+mod ocr;
+pub mod ods;
+mod posicion;
+pub mod validacion;
+
+pub use config::{
+    parsear_paginas, Config, EstrategiaMonto, ExtractorCampo, Extractores, ReglaPrefijo,
+    SeleccionPaginas,
+};
+pub use error::ProcError;
+pub use excel::{
+    estilo_tabla, guardar_y_formatear_excel, normalizar_salida_excel, validar_salida_excel,
+    ColumnaPdf, Idioma, OpcionesExcel,
+};
+pub use exportar::{guardar_csv, guardar_json, guardar_json_plano};
+pub use extraccion::{
+    acortar_nombre, contar_montos, extraer_expediente, extraer_expediente_y_año, extraer_fecha,
+    extraer_juzgado, extraer_monto, extraer_monto_original, extraer_monto_y_moneda, extraer_montos,
+    extraer_numero_cheque, extraer_numeros_cheque, extraer_texto_entre_comillas,
+    extraer_textos_entre_comillas, limpiar_nombre, normalizar_nro, redondear_monto, validar_cheque,
+    Confianza, Expediente,
+};
+pub use informe::{Estadisticas, Evento, Informe, Verbosidad};
+pub use ods::guardar_ods;
+pub use validacion::{
+    cargar_montos_esperados, cotejar_montos, detectar_duplicados, filtrar_por_expediente,
+    marcar_duplicados, totalizar, unir_continuaciones, Duplicado, Totales, TOLERANCIA_MONTO,
+};
+
+use cache::CacheTexto;
+use chrono::NaiveDate;
+use formulario::Formulario;
+use indicatif::{ProgressBar, ProgressStyle};
+use lopdf::Document;
+use rayon::prelude::*;
+use serde::{Serialize, Serializer};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// Versión del programa y, si se compiló desde un repositorio git, el commit:
+/// "0.1.0 (1a2b3c4)".
+pub fn version() -> String {
+    match option_env!("GIT_HASH") {
+        Some(hash) => format!("{} ({})", env!("CARGO_PKG_VERSION"), hash),
+        None => env!("CARGO_PKG_VERSION").to_string(),
+    }
+}
+
+/// Datos extraídos de una página del PDF
+///
+/// Al serializar, `año` se escribe como `anio` para que las claves sean ASCII.
+#[derive(Debug, Clone, Serialize)]
+pub struct DatosPagina {
+    pub nombre: String,
+    pub expediente: String,
+    #[serde(rename = "anio")]
+    pub año: String,
+    pub monto: String,
+    /// Texto del monto tal como aparece en la página, antes de normalizarlo
+    /// ("($1.234,56)"); vacío si no se encontró monto
+    pub monto_original: String,
+    pub cheque: String,
+    /// Moneda del monto ("ARS" o "USD"); vacía si no se encontró monto
+    pub moneda: String,
+    /// Juzgado que emite la orden; vacío si no se encontró
+    pub juzgado: String,
+    /// Fecha de emisión indicada en la página
+    pub fecha: Option<NaiveDate>,
+    /// Nombre del archivo PDF de origen
+    pub archivo: String,
+    /// Número de página dentro del PDF de origen
+    pub pagina: usize,
+    /// Advertencias generadas durante la extracción y los controles
+    pub observaciones: Vec<String>,
+    /// Confianza de cada campo extraído
+    pub confianza: ConfianzaCampos,
+    /// Patrón que encontró el expediente (ver [`Expediente::patron`]), para
+    /// diagnosticar los patrones; `None` en los registros leídos de un Excel
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub patron_expediente: Option<usize>,
+    /// Campos agregados por los extractores propios, como (nombre, valor)
+    #[serde(
+        rename = "extra",
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "serializar_campos_extra"
+    )]
+    pub campos_extra: Vec<(String, String)>,
+}
+
+/// Escribe los campos adicionales como un objeto, en el orden de los extractores.
+fn serializar_campos_extra<S: Serializer>(
+    campos: &[(String, String)],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(campos.iter().map(|(nombre, valor)| (nombre, valor)))
+}
+
+/// Nombres de los campos adicionales de los registros, en el orden en que
+/// aparecen por primera vez; cada uno es una columna más de la salida.
+pub fn nombres_campos_extra(datos: &[DatosPagina]) -> Vec<&str> {
+    let mut vistos = HashSet::new();
+    datos
+        .iter()
+        .flat_map(|dato| dato.campos_extra.iter().map(|(nombre, _)| nombre.as_str()))
+        .filter(|nombre| vistos.insert(*nombre))
+        .collect()
+}
+
+/// Comienzo de los avisos que el programa agrega a las observaciones. Cada aviso
+/// tiene una etiqueta fija para poder filtrarlo ("Aviso: monto no encontrado",
+/// "Aviso: varios montos (9000.00, 12000.00, se tomó el primero)"); el detalle
+/// va después, entre paréntesis.
+///
+/// Las notas que solo dicen de dónde salió un dato ("Unido con la página 3",
+/// "Tomado del formulario: monto", "Nombre completo: ...") no son avisos.
+pub const PREFIJO_AVISO: &str = "Aviso: ";
+
+/// Aviso con su etiqueta fija y, si no está vacío, el detalle entre paréntesis.
+pub(crate) fn aviso(etiqueta: &str, detalle: &str) -> String {
+    if detalle.is_empty() {
+        format!("{}{}", PREFIJO_AVISO, etiqueta)
+    } else {
+        format!("{}{} ({})", PREFIJO_AVISO, etiqueta, detalle)
+    }
+}
+
+/// Aviso del campo que quedó con el valor de respaldo.
+fn aviso_no_encontrado(campo: &str) -> String {
+    aviso(&format!("{} no encontrado", campo), "")
+}
+
+impl DatosPagina {
+    /// Deja en las observaciones un aviso "Aviso: <campo> no encontrado" por cada
+    /// campo con el valor de respaldo, y quita los de los campos que ya se
+    /// completaron (al unir continuaciones o corregir en la revisión).
+    pub fn actualizar_avisos(&mut self) {
+        let avisos: Vec<String> = self
+            .confianza
+            .respaldos()
+            .into_iter()
+            .map(aviso_no_encontrado)
+            .collect();
+        let campos = ["nombre", "expediente", "año", "monto", "cheque"];
+        self.observaciones
+            .retain(|o| !campos.iter().any(|campo| *o == aviso_no_encontrado(campo)));
+        self.observaciones.extend(avisos);
+    }
+
+    /// Valor del campo adicional con ese nombre; vacío si el registro no lo tiene.
+    pub fn campo_extra(&self, nombre: &str) -> &str {
+        self.campos_extra
+            .iter()
+            .find(|(n, _)| n == nombre)
+            .map_or("", |(_, valor)| valor.as_str())
+    }
+}
+
+/// Confianza de los campos principales de un registro.
+///
+/// En los registros leídos de un Excel existente se deduce de los valores.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct ConfianzaCampos {
+    pub nombre: Confianza,
+    pub expediente: Confianza,
+    #[serde(rename = "anio")]
+    pub año: Confianza,
+    pub monto: Confianza,
+    pub cheque: Confianza,
+}
+
+impl ConfianzaCampos {
+    /// Nombres de los campos que quedaron con el valor de respaldo.
+    pub fn respaldos(&self) -> Vec<&'static str> {
+        [
+            ("nombre", self.nombre),
+            ("expediente", self.expediente),
+            ("año", self.año),
+            ("monto", self.monto),
+            ("cheque", self.cheque),
+        ]
+        .into_iter()
+        .filter(|(_, confianza)| *confianza == Confianza::Respaldo)
+        .map(|(campo, _)| campo)
+        .collect()
+    }
+}
+
+/// Carga el documento y lo desencripta si hace falta.
+///
+/// Sin contraseña configurada se intenta con la contraseña de usuario vacía,
+/// que es como suelen venir los PDF protegidos solo contra edición.
+fn cargar_documento(ruta_archivo: &Path, config: &Config) -> Result<Document, ProcError> {
+    let doc = Document::load(ruta_archivo).map_err(|source| ProcError::PdfLoad {
+        path: ruta_archivo.to_path_buf(),
+        source,
+    })?;
+    desencriptar(doc, ruta_archivo, config)
+}
+
+/// Igual que [`cargar_documento`], pero desde el contenido del PDF en memoria.
+fn cargar_documento_mem(
+    contenido: &[u8],
+    ruta_archivo: &Path,
+    config: &Config,
+) -> Result<Document, ProcError> {
+    let doc = Document::load_mem(contenido).map_err(|source| ProcError::PdfLoad {
+        path: ruta_archivo.to_path_buf(),
+        source,
+    })?;
+    desencriptar(doc, ruta_archivo, config)
+}
+
+fn desencriptar(
+    mut doc: Document,
+    ruta_archivo: &Path,
+    config: &Config,
+) -> Result<Document, ProcError> {
+    let path = ruta_archivo.to_path_buf();
+
+    if doc.is_encrypted() {
+        match &config.contraseña {
+            Some(contraseña) => {
+                if doc.decrypt(contraseña).is_err() {
+                    return Err(ProcError::ContraseñaIncorrecta { path });
+                }
+            }
+            None => {
+                if doc.decrypt("").is_err() {
+                    return Err(ProcError::PdfProtegido { path });
+                }
+            }
+        }
+    }
+
+    Ok(doc)
+}
+
+/// Une las líneas, lleva las abreviaturas de "número" a "Nro." (ver
+/// [`normalizar_nro`]) y quita los símbolos que no son ASCII ni alfanuméricos.
+///
+/// El texto se lleva a la forma NFC para que una letra acentuada escrita con
+/// un carácter combinante se compare igual que la precompuesta. Con
+/// `config.plegar_acentos` además se quitan los acentos.
+fn limpiar_texto(texto: &str, config: &Config) -> String {
+    // Antes de quitar acentos y símbolos: el "°" de "N°" no es alfanumérico y
+    // el "Â" de un "NÂº" mal decodificado se plegaría a "A"
+    let texto = normalizar_nro(texto).replace('\n', " ");
+    let texto: String = if config.plegar_acentos {
+        texto
+            .nfd()
+            .filter(|c| !is_combining_mark(*c))
+            .nfc()
+            .collect()
+    } else {
+        texto.nfc().collect()
+    };
+    texto
+        .chars()
+        .filter(|c| c.is_ascii() || c.is_alphanumeric() || c.is_whitespace())
+        .collect()
+}
+
+/// Valor de un campo que no se encontró: el número de página, o vacío con
+/// `config.vacio_si_falta`.
+fn respaldo(p: usize, config: &Config) -> String {
+    if config.vacio_si_falta {
+        String::new()
+    } else {
+        (p + 1).to_string()
+    }
+}
+
+/// Extrae el texto limpio de una página.
+///
+/// Con `config.ocr`, una página sin texto suficiente se rasteriza y se lee con OCR;
+/// para eso el PDF tiene que estar en disco (`ruta_archivo`).
+/// Si no se pudo leer, devuelve el evento de la página fallida.
+fn leer_pagina(
+    doc: &Document,
+    ruta_archivo: Option<&Path>,
+    cache: Option<&CacheTexto>,
+    page_num: u32,
+    archivo: &str,
+    config: &Config,
+) -> Result<String, Evento> {
+    let fallida = |mensaje: String| Evento::Fallida {
+        archivo: archivo.to_string(),
+        pagina: page_num,
+        mensaje,
+    };
+    let texto_pagina = match extraer_texto_pagina(doc, cache, page_num, config) {
+        Ok(t) => t,
+        Err(_) if config.ocr => String::new(),
+        Err(e) => return Err(fallida(e.to_string())),
+    };
+
+    let texto = limpiar_texto(&texto_pagina, config);
+
+    // Página escaneada: reconocer el texto de la imagen
+    if config.ocr && texto.chars().count() < config.min_caracteres {
+        let texto_ocr = match ruta_archivo {
+            Some(ruta) => ocr::reconocer_pagina(ruta, page_num),
+            None => Err("el PDF no se leyó de un archivo".to_string()),
+        };
+        return match texto_ocr {
+            Ok(texto_ocr) => Ok(limpiar_texto(&texto_ocr, config)),
+            Err(mensaje) => Err(fallida(format!("OCR: {}", mensaje))),
+        };
+    }
+
+    Ok(texto)
+}
+
+/// Extrae el texto de una página; en varias columnas, por posición. Con caché,
+/// se toma de ahí si ya se había extraído.
+fn extraer_texto_pagina(
+    doc: &Document,
+    cache: Option<&CacheTexto>,
+    page_num: u32,
+    config: &Config,
+) -> lopdf::Result<String> {
+    let extraer = || {
+        if config.columnas_texto > 1 {
+            posicion::extraer_texto_por_columnas(doc, page_num, config.columnas_texto)
+        } else {
+            doc.extract_text(&[page_num])
+        }
+    };
+    match cache {
+        Some(cache) => cache.texto(page_num, extraer),
+        None => extraer(),
+    }
+}
+
+/// Guarda el texto que reciben los extractores en `directorio/page_<n>.txt`.
+///
+/// Si no se pudo, devuelve el mensaje para la advertencia.
+fn volcar_texto(directorio: &Path, page_num: u32, texto: &str) -> Result<(), String> {
+    let ruta = directorio.join(format!("page_{}.txt", page_num));
+    std::fs::create_dir_all(directorio)
+        .and_then(|_| std::fs::write(&ruta, texto))
+        .map_err(|e| format!("no se pudo guardar el texto en {:?}: {}", ruta, e))
+}
+
+/// Aplica los extractores al texto ya limpio de una página.
+///
+/// Una página con menos de `config.min_caracteres` caracteres se omite, y con
+/// `config.omitir_sin_datos` también la que no tiene monto "($...)" ni expediente,
+/// salvo que tenga campos de formulario: esos campos reemplazan a los del texto.
+fn procesar_texto(
+    texto: &str,
+    formulario: Option<&Formulario>,
+    p: usize,
+    page_num: u32,
+    archivo: &str,
+    config: &Config,
+) -> (Evento, Vec<DatosPagina>) {
+    // Saltar páginas cortas (se cuentan caracteres, no bytes)
+    let caracteres = texto.chars().count();
+    if caracteres < config.min_caracteres && formulario.is_none() {
+        let evento = Evento::Omitida {
+            archivo: archivo.to_string(),
+            pagina: page_num,
+            caracteres,
+        };
+        return (evento, Vec::new());
+    }
+
+    // Todos los candidatos a nombre, sin repetir, para elegir y para medir la confianza
+    let candidatos = extraer_textos_entre_comillas(texto, config);
+    let confianza_nombre = match candidatos.len() {
+        0 => Confianza::Respaldo,
+        1 => Confianza::Encontrado,
+        _ if config.todas_las_caratulas => Confianza::Encontrado,
+        _ => Confianza::Ambiguo,
+    };
+    let nombres = if candidatos.is_empty() {
+        vec![respaldo(p, config)]
+    } else if config.todas_las_caratulas {
+        candidatos
+    } else {
+        candidatos.into_iter().take(1).collect()
+    };
+    let Expediente {
+        numero: expediente,
+        año,
+        encontrado: expediente_encontrado,
+        año_dudoso,
+        año_fuera_de_rango,
+        relajado: expediente_relajado,
+        patron: patron_expediente,
+    } = extraer_expediente(texto, p, config);
+    let (mut monto, moneda) = extraer_monto_y_moneda(texto, p);
+    let montos = extraer_montos(texto);
+    if config.omitir_sin_datos
+        && !expediente_encontrado
+        && montos.is_empty()
+        && formulario.is_none()
+    {
+        let evento = Evento::SinDatos {
+            archivo: archivo.to_string(),
+            pagina: page_num,
+            caracteres,
+        };
+        return (evento, Vec::new());
+    }
+    if let Some(elegido) = config.estrategia_monto.elegir(&montos) {
+        monto = elegido.clone();
+    }
+    let monto_original = extraer_monto_original(texto, &monto);
+    // Sin moneda, el monto es el número de página de respaldo y no se redondea
+    if let Some(decimales) = config.decimales_monto.filter(|_| !moneda.is_empty()) {
+        monto = redondear_monto(&monto, decimales);
+    }
+    let cheques = extraer_numeros_cheque(texto, config);
+    let cheque = cheques
+        .first()
+        .cloned()
+        .unwrap_or_else(|| (p + 1).to_string());
+    let juzgado = extraer_juzgado(texto);
+    let fecha = extraer_fecha(texto);
+
+    let confianza = ConfianzaCampos {
+        nombre: confianza_nombre,
+        expediente: if !expediente_encontrado {
+            Confianza::Respaldo
+        } else if expediente_relajado {
+            Confianza::Ambiguo
+        } else {
+            Confianza::Encontrado
+        },
+        año: if año.trim().is_empty() {
+            Confianza::Respaldo
+        } else if año_dudoso {
+            Confianza::Ambiguo
+        } else {
+            Confianza::Encontrado
+        },
+        monto: if moneda.is_empty() {
+            Confianza::Respaldo
+        } else if contar_montos(texto) > 1 {
+            Confianza::Ambiguo
+        } else {
+            Confianza::Encontrado
+        },
+        cheque: if cheque == (p + 1).to_string() {
+            Confianza::Respaldo
+        } else {
+            Confianza::Encontrado
+        },
+    };
+
+    let evento = Evento::Procesada {
+        archivo: archivo.to_string(),
+        pagina: page_num,
+        caracteres,
+    };
+    let mut datos = DatosPagina {
+        nombre: String::new(),
+        expediente,
+        año,
+        monto,
+        monto_original,
+        cheque,
+        moneda,
+        juzgado,
+        fecha,
+        archivo: archivo.to_string(),
+        pagina: page_num as usize,
+        observaciones: Vec::new(),
+        confianza,
+        patron_expediente,
+        campos_extra: config
+            .extractores
+            .0
+            .iter()
+            .map(|extractor| extractor(texto))
+            .collect(),
+    };
+    // Los campos del formulario se prefieren a los extraídos del texto
+    let usados = formulario
+        .map(|formulario| formulario.aplicar(&mut datos, p, config))
+        .unwrap_or_default();
+    let del_formulario = |campo: &str| usados.contains(&campo);
+    if config.vacio_si_falta {
+        if datos.confianza.expediente == Confianza::Respaldo {
+            datos.expediente.clear();
+        }
+        if datos.confianza.monto == Confianza::Respaldo {
+            datos.monto.clear();
+        }
+        if datos.confianza.cheque == Confianza::Respaldo {
+            datos.cheque.clear();
+        }
+    }
+    if expediente_relajado && !del_formulario("expediente") {
+        datos
+            .observaciones
+            .push(aviso("expediente con patrón relajado", ""));
+    }
+    if montos.len() > 1 && !del_formulario("monto") {
+        let detalle = format!(
+            "{}, se tomó {}",
+            montos.join(", "),
+            config.estrategia_monto.nombre()
+        );
+        datos.observaciones.push(aviso("varios montos", &detalle));
+    }
+    if confianza_nombre == Confianza::Ambiguo && !del_formulario("nombre") {
+        datos.observaciones.push(aviso("varios autos", ""));
+    }
+    if año_fuera_de_rango && !del_formulario("expediente") {
+        datos.observaciones.push(aviso("año fuera de rango", ""));
+    }
+    if año_dudoso && !del_formulario("expediente") {
+        let detalle = format!("{}, no está separado del número de expediente", datos.año);
+        datos.observaciones.push(aviso("año dudoso", &detalle));
+    }
+    let (nombres, cheques) = match (del_formulario("nombre"), del_formulario("cheque")) {
+        (true, true) => (vec![datos.nombre.clone()], vec![datos.cheque.clone()]),
+        (true, false) => (vec![datos.nombre.clone()], cheques),
+        (false, true) => (nombres, vec![datos.cheque.clone()]),
+        (false, false) => (nombres, cheques),
+    };
+    let cheques = if cheques.len() > 1 && config.todos_los_cheques {
+        let total = cheques.len();
+        cheques
+            .into_iter()
+            .enumerate()
+            .map(|(i, cheque)| {
+                let detalle = format!(
+                    "{} de {}, revisar qué parte del monto le corresponde",
+                    i + 1,
+                    total
+                );
+                let observacion = aviso("varios cheques", &detalle);
+                (cheque, Some(observacion))
+            })
+            .collect()
+    } else {
+        if cheques.len() > 1 {
+            let detalle = format!("{} en la página, se tomó el primero", cheques.len());
+            datos.observaciones.push(aviso("varios cheques", &detalle));
+        }
+        vec![(datos.cheque.clone(), None)]
+    };
+    let datos = nombres
+        .into_iter()
+        .flat_map(|nombre| {
+            cheques
+                .iter()
+                .map(move |(cheque, observacion)| (nombre.clone(), cheque, observacion))
+        })
+        .map(|(nombre, cheque, observacion)| {
+            let mut dato = DatosPagina {
+                nombre,
+                cheque: cheque.clone(),
+                ..datos.clone()
+            };
+            dato.observaciones.extend(observacion.clone());
+            if let Some(motivo) = config
+                .validar_cheques
+                .then(|| validar_cheque(&dato.cheque))
+                .flatten()
+            {
+                dato.observaciones.push(motivo);
+                dato.confianza.cheque = Confianza::Ambiguo;
+            }
+            if let Some(acortado) = config
+                .largo_max_nombre
+                .and_then(|largo| acortar_nombre(&dato.nombre, largo))
+            {
+                let completo = std::mem::replace(&mut dato.nombre, acortado);
+                dato.observaciones
+                    .push(format!("Nombre completo: {}", completo));
+            }
+            dato.actualizar_avisos();
+            dato
+        })
+        .collect();
+    (evento, datos)
+}
+
+/// Promedio de caracteres por página por debajo del cual un PDF sin registros
+/// se considera escaneado.
+const MIN_CARACTERES_ESCANEO: usize = 20;
+
+/// Caracteres, sin contar espacios, que necesita una página para servir de muestra
+/// al revisar la codificación de las fuentes.
+const MIN_CARACTERES_MUESTRA: usize = 100;
+
+/// Revisa con la primera página que tenga texto suficiente si las fuentes se pueden
+/// decodificar. Devuelve la página y la proporción de símbolos si el texto parece
+/// basura, lo que suele resolverse con OCR.
+fn revisar_codificacion(
+    doc: &Document,
+    cache: Option<&CacheTexto>,
+    pages: &[(usize, u32)],
+    config: &Config,
+) -> Option<(u32, f64)> {
+    let (page_num, texto) = pages.iter().find_map(|&(_, page_num)| {
+        let texto = extraer_texto_pagina(doc, cache, page_num, config).ok()?;
+        let caracteres = texto.chars().filter(|c| !c.is_whitespace()).count();
+        (caracteres >= MIN_CARACTERES_MUESTRA).then_some((page_num, texto))
+    })?;
+    let proporcion = extraccion::proporcion_simbolos(&texto);
+    (proporcion > extraccion::PROPORCION_MAX_SIMBOLOS).then_some((page_num, proporcion))
+}
+
+/// Procesa un archivo PDF y extrae la información relevante de cada página.
+///
+/// Las páginas se procesan en paralelo; el resultado conserva el orden del documento.
+/// Con `config.paginas` solo se procesan esas páginas, y pedir una que no existe es
+/// un error ([`ProcError::PaginasInexistentes`]).
+/// Un documento con menos de `config.min_paginas` o más de `config.max_paginas`
+/// páginas se rechaza con [`ProcError::CantidadPaginas`].
+/// Una página cuyo texto no puede extraerse se informa y se omite. Si el documento
+/// casi no tiene texto en ninguna página se devuelve [`ProcError::SinTexto`].
+pub fn procesar_pdf(ruta_archivo: &Path, config: &Config) -> Result<Vec<DatosPagina>, ProcError> {
+    procesar_pdf_con_informe(ruta_archivo, config, &mut Informe::default())
+}
+
+/// Igual que [`procesar_pdf`], pero con extractores propios que agregan campos a
+/// cada registro, además de los de `config.extractores`.
+///
+/// Cada extractor recibe el texto limpio de la página y devuelve el nombre y el
+/// valor del campo; los campos se escriben como columnas adicionales al final de
+/// la hoja PDF, del CSV y como el objeto `extra` del JSON.
+pub fn procesar_pdf_con_hooks(
+    ruta_archivo: &Path,
+    config: &Config,
+    hooks: &[ExtractorCampo],
+) -> Result<Vec<DatosPagina>, ProcError> {
+    let mut config = config.clone();
+    config.extractores.0.extend(hooks.iter().cloned());
+    procesar_pdf(ruta_archivo, &config)
+}
+
+/// Extensiones de las imágenes que se aceptan en lugar de un PDF.
+pub const EXTENSIONES_IMAGEN: [&str; 5] = ["tif", "tiff", "png", "jpg", "jpeg"];
+
+/// Indica si la ruta es una imagen (TIFF, PNG o JPEG) según su extensión.
+pub fn es_imagen(ruta: &Path) -> bool {
+    ruta.extension().is_some_and(|ext| {
+        EXTENSIONES_IMAGEN
+            .iter()
+            .any(|imagen| ext.eq_ignore_ascii_case(imagen))
+    })
+}
+
+/// Igual que [`procesar_pdf`], pero registra en `informe` lo ocurrido con cada página.
+///
+/// Si la ruta es una imagen (ver [`es_imagen`]) se procesa con [`procesar_imagen_con_informe`].
+pub fn procesar_pdf_con_informe(
+    ruta_archivo: &Path,
+    config: &Config,
+    informe: &mut Informe,
+) -> Result<Vec<DatosPagina>, ProcError> {
+    if es_imagen(ruta_archivo) {
+        return procesar_imagen_con_informe(ruta_archivo, config, informe);
+    }
+    let doc = cargar_documento(ruta_archivo, config)?;
+    procesar_documento(&doc, ruta_archivo, true, config, informe)
+}
+
+/// Procesa una imagen escaneada (un TIFF de una o varias páginas, PNG o JPEG)
+/// leyendo el texto de cada página con OCR, con los mismos extractores que un PDF.
+///
+/// Siempre usa OCR, aunque no se haya pedido `config.ocr`. Con `config.paginas`
+/// solo se procesan esas páginas.
+pub fn procesar_imagen_con_informe(
+    ruta_archivo: &Path,
+    config: &Config,
+    informe: &mut Informe,
+) -> Result<Vec<DatosPagina>, ProcError> {
+    let archivo = ruta_archivo
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let paginas =
+        ocr::reconocer_paginas_imagen(ruta_archivo).map_err(|mensaje| ProcError::Imagen {
+            path: ruta_archivo.to_path_buf(),
+            mensaje,
+        })?;
+    informe.paginas_totales += paginas.len();
+
+    let mut lista_datos = Vec::new();
+    for (p, texto) in paginas.into_iter().enumerate() {
+        let page_num = (p + 1) as u32;
+        if config
+            .paginas
+            .as_ref()
+            .is_some_and(|seleccion| !seleccion.contiene(page_num))
+        {
+            continue;
+        }
+        let (evento, datos) = match texto {
+            Ok(texto) => {
+                let texto = limpiar_texto(&texto, config);
+                if let Some(directorio) = &config.volcado_texto {
+                    if let Err(mensaje) = volcar_texto(directorio, page_num, &texto) {
+                        informe.registrar(Evento::Aviso {
+                            archivo: archivo.clone(),
+                            mensaje,
+                        });
+                    }
+                }
+                procesar_texto(&texto, None, p, page_num, &archivo, config)
+            }
+            Err(mensaje) => {
+                let evento = Evento::Fallida {
+                    archivo: archivo.clone(),
+                    pagina: page_num,
+                    mensaje: format!("OCR: {}", mensaje),
+                };
+                (evento, Vec::new())
+            }
+        };
+        informe.registrar(evento);
+        lista_datos.extend(datos);
+    }
+    Ok(lista_datos)
+}
+
+/// Igual que [`procesar_pdf`], pero a partir del contenido del PDF ya leído,
+/// por ejemplo desde la entrada estándar.
+///
+/// `nombre` se usa como nombre de archivo en los registros y en los errores.
+pub fn procesar_pdf_mem(
+    contenido: &[u8],
+    nombre: &str,
+    config: &Config,
+) -> Result<Vec<DatosPagina>, ProcError> {
+    procesar_pdf_mem_con_informe(contenido, nombre, config, &mut Informe::default())
+}
+
+/// Igual que [`procesar_pdf_mem`], pero registra en `informe` lo ocurrido con cada página.
+pub fn procesar_pdf_mem_con_informe(
+    contenido: &[u8],
+    nombre: &str,
+    config: &Config,
+    informe: &mut Informe,
+) -> Result<Vec<DatosPagina>, ProcError> {
+    let ruta = Path::new(nombre);
+    let doc = cargar_documento_mem(contenido, ruta, config)?;
+    procesar_documento(&doc, ruta, false, config, informe)
+}
+
+/// Procesa las páginas de un documento ya cargado.
+///
+/// `en_disco` indica si `ruta_archivo` es un archivo real, necesario para el OCR.
+fn procesar_documento(
+    doc: &Document,
+    ruta_archivo: &Path,
+    en_disco: bool,
+    config: &Config,
+    informe: &mut Informe,
+) -> Result<Vec<DatosPagina>, ProcError> {
+    let ruta_ocr = en_disco.then_some(ruta_archivo);
+    let archivo = ruta_archivo
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    // Obtener todas las páginas del documento, con su posición
+    let mut pages: Vec<(usize, u32)> = doc.get_pages().into_keys().enumerate().collect();
+    let total_pages = pages.len();
+
+    // Rechazar documentos con una cantidad de páginas inesperada
+    let max_paginas = config.max_paginas.unwrap_or(usize::MAX);
+    if total_pages < config.min_paginas || total_pages > max_paginas {
+        let rango = match config.max_paginas {
+            Some(max) => format!("{} a {}", config.min_paginas, max),
+            None => format!("al menos {}", config.min_paginas),
+        };
+        return Err(ProcError::CantidadPaginas {
+            path: ruta_archivo.to_path_buf(),
+            paginas: total_pages,
+            rango,
+        });
+    }
+
+    // Quedarse con las páginas pedidas, si se eligió un rango
+    if let Some(seleccion) = &config.paginas {
+        let inexistentes = seleccion.fuera_de(total_pages as u32);
+        if !inexistentes.is_empty() {
+            return Err(ProcError::PaginasInexistentes {
+                path: ruta_archivo.to_path_buf(),
+                total: total_pages,
+                paginas: inexistentes.join(", "),
+            });
+        }
+        pages.retain(|&(_, page_num)| seleccion.contiene(page_num));
+    }
+    let num_pages = pages.len();
+
+    informe.paginas_totales += total_pages;
+
+    let silencioso = informe.verbosidad == Verbosidad::Silencioso;
+    if !silencioso {
+        if num_pages == total_pages {
+            println!("El PDF tiene {} páginas", num_pages);
+        } else {
+            println!(
+                "El PDF tiene {} páginas; se procesan {}",
+                total_pages, num_pages
+            );
+        }
+    }
+
+    // El texto ya extraído en una corrida anterior se toma del caché
+    let cache = config
+        .cache_texto
+        .as_deref()
+        .filter(|_| en_disco)
+        .and_then(|directorio| CacheTexto::abrir(directorio, ruta_archivo, config.columnas_texto));
+    let cache = cache.as_ref();
+
+    // Un texto lleno de símbolos parece una extracción vacía pero válida
+    if let Some((page_num, proporcion)) = revisar_codificacion(doc, cache, &pages, config) {
+        informe.registrar(Evento::Aviso {
+            archivo: archivo.clone(),
+            mensaje: format!(
+                "en {} pág. {}, el {:.0}% del texto extraído son símbolos; \
+                 las fuentes pueden tener una codificación que no se puede leer. \
+                 Revisar los datos o probar con --ocr",
+                archivo,
+                page_num,
+                proporcion * 100.0
+            ),
+        });
+    }
+
+    // En los PDF rellenables, los datos de los campos se prefieren al texto
+    let formularios = formulario::leer(doc);
+    if !formularios.is_empty() && !silencioso {
+        println!(
+            "El PDF tiene campos de formulario en {} páginas; se usan en lugar del texto",
+            formularios.len()
+        );
+    }
+
+    let barra = barra_de_progreso(num_pages, silencioso);
+    let verbosidad = informe.verbosidad;
+    // Los volcados de texto que fallaron, para advertirlo al terminar
+    let volcados_fallidos = Mutex::new(Vec::new());
+    let procesar = |&(p, page_num): &(usize, u32)| {
+        // Los campos del formulario reemplazan a los que se extraen del texto
+        let formulario = formularios.get(&page_num);
+        let resultado = match leer_pagina(doc, ruta_ocr, cache, page_num, &archivo, config) {
+            Ok(texto) => {
+                if let Some(directorio) = &config.volcado_texto {
+                    if let Err(mensaje) = volcar_texto(directorio, page_num, &texto) {
+                        volcados_fallidos.lock().unwrap().push(mensaje);
+                    }
+                }
+                procesar_texto(&texto, formulario, p, page_num, &archivo, config)
+            }
+            Err(evento) => (evento, Vec::new()),
+        };
+        // Cada página se muestra al terminarla, sobre la barra de progreso
+        if verbosidad.muestra(&resultado.0) {
+            barra.suspend(|| println!("{}", resultado.0));
+        }
+        barra.set_message(page_num.to_string());
+        barra.inc(1);
+        resultado
+    };
+    let resultados: Vec<(Evento, Vec<DatosPagina>)> = match config.muestra {
+        Some(muestra) => procesar_muestra(&pages, muestra, procesar),
+        None => pages.par_iter().map(procesar).collect(),
+    };
+    barra.finish_and_clear();
+    for mensaje in volcados_fallidos.into_inner().unwrap() {
+        informe.registrar(Evento::Aviso {
+            archivo: archivo.clone(),
+            mensaje,
+        });
+    }
+
+    // Que la salida parcial no se confunda con la completa
+    if resultados.len() < num_pages {
+        informe.registrar(Evento::Aviso {
+            archivo: archivo.clone(),
+            mensaje: format!(
+                "la muestra se detuvo en {} tras {} páginas con registros; se procesaron \
+                 {} de {} páginas y los datos están incompletos",
+                archivo,
+                config.muestra.unwrap_or_default(),
+                resultados.len(),
+                num_pages
+            ),
+        });
+    }
+
+    if let Some(Err(e)) = cache.map(CacheTexto::guardar) {
+        informe.registrar(Evento::Aviso {
+            archivo: archivo.clone(),
+            mensaje: format!("no se pudo guardar el caché de texto: {}", e),
+        });
+    }
+
+    let mut lista_datos = Vec::new();
+    let mut caracteres_totales = 0;
+    for (evento, datos) in resultados {
+        if let Evento::Procesada { caracteres, .. }
+        | Evento::Omitida { caracteres, .. }
+        | Evento::SinDatos { caracteres, .. } = &evento
+        {
+            caracteres_totales += caracteres;
+        }
+        informe.registrar_mostrado(evento);
+        lista_datos.extend(datos);
+    }
+
+    // Un documento con páginas pero sin capa de texto suele ser un escaneo
+    if num_pages > 0
+        && lista_datos.is_empty()
+        && caracteres_totales < MIN_CARACTERES_ESCANEO * num_pages
+    {
+        return Err(ProcError::SinTexto {
+            path: ruta_archivo.to_path_buf(),
+            paginas: num_pages,
+        });
+    }
+
+    Ok(lista_datos)
+}
+
+/// Procesa las páginas en tandas de tantas como hilos, en paralelo dentro de
+/// cada tanda, hasta que `muestra` páginas hayan producido registros.
+///
+/// Devuelve los resultados en el orden del documento, hasta esa página inclusive,
+/// como si se hubieran procesado una por una.
+fn procesar_muestra<F>(
+    pages: &[(usize, u32)],
+    muestra: usize,
+    procesar: F,
+) -> Vec<(Evento, Vec<DatosPagina>)>
+where
+    F: Fn(&(usize, u32)) -> (Evento, Vec<DatosPagina>) + Sync,
+{
+    let mut resultados = Vec::new();
+    let mut con_registros = 0;
+    for tanda in pages.chunks(rayon::current_num_threads().max(1)) {
+        let procesadas: Vec<_> = tanda.par_iter().map(&procesar).collect();
+        for resultado in procesadas {
+            if !resultado.1.is_empty() {
+                con_registros += 1;
+            }
+            resultados.push(resultado);
+            if con_registros >= muestra {
+                return resultados;
+            }
+        }
+    }
+    resultados
+}
+
+/// Crea la barra de progreso por páginas; oculta si `silencioso`.
+fn barra_de_progreso(num_pages: usize, silencioso: bool) -> ProgressBar {
+    if silencioso {
+        return ProgressBar::hidden();
+    }
+    let barra = ProgressBar::new(num_pages as u64);
+    if let Ok(estilo) =
+        ProgressStyle::with_template("{bar:40} {pos}/{len} páginas (pág. {msg}) ETA {eta}")
+    {
+        barra.set_style(estilo);
+    }
+    barra
+}
+
+/// Procesa todos los PDF (y las imágenes escaneadas) de un directorio y concatena
+/// los resultados.
+///
+/// Los archivos se recorren en orden alfabético. Un PDF que no se puede
+/// procesar se omite con una advertencia en lugar de cortar el lote.
+pub fn procesar_directorio(dir: &Path, config: &Config) -> Result<Vec<DatosPagina>, ProcError> {
+    procesar_directorio_con_informe(dir, config, &mut Informe::default())
+}
+
+/// Igual que [`procesar_directorio`], pero registra en `informe` lo ocurrido con cada página.
+pub fn procesar_directorio_con_informe(
+    dir: &Path,
+    config: &Config,
+    informe: &mut Informe,
+) -> Result<Vec<DatosPagina>, ProcError> {
+    let mut archivos: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|source| ProcError::Directorio {
+            path: dir.to_path_buf(),
+            source,
+        })?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.is_file()
+                && (es_imagen(p)
+                    || p.extension()
+                        .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf")))
+        })
+        .collect();
+    archivos.sort();
+
+    let silencioso = informe.verbosidad == Verbosidad::Silencioso;
+    if !silencioso {
+        println!(
+            "Se encontraron {} archivos PDF o imágenes en {:?}",
+            archivos.len(),
+            dir
+        );
+    }
+
+    let mut lista_datos = Vec::new();
+    for ruta in &archivos {
+        if !silencioso {
+            println!("\nProcesando: {:?}", ruta);
+        }
+        // El texto de cada archivo va a su propio subdirectorio para no pisarse
+        let mut config_archivo = config.clone();
+        if let (Some(directorio), Some(nombre)) = (&config.volcado_texto, ruta.file_stem()) {
+            config_archivo.volcado_texto = Some(directorio.join(nombre));
+        }
+        match procesar_pdf_con_informe(ruta, &config_archivo, informe) {
+            Ok(datos) => lista_datos.extend(datos),
+            Err(e) => informe.registrar(Evento::ArchivoFallido {
+                archivo: ruta.display().to_string(),
+                mensaje: e.to_string(),
+            }),
+        }
+    }
+
+    Ok(lista_datos)
+}
@@ -4,546 +4,1156 @@
 //! nombre, expediente, año, monto y número de cheque.
 //! Luego guarda los datos en un archivo Excel y aplica formato.
 
-use calamine::{open_workbook, Reader, Xlsx};
-use lopdf::Document;
+use pdf_procuracion::extraccion::MONEDA_PESOS;
+use pdf_procuracion::{
+    cargar_montos_esperados, cotejar_montos, estilo_tabla, filtrar_por_expediente, guardar_csv,
+    guardar_json, guardar_json_plano, guardar_ods, guardar_y_formatear_excel, marcar_duplicados,
+    normalizar_salida_excel, parsear_paginas, procesar_directorio_con_informe,
+    procesar_pdf_con_informe, procesar_pdf_mem_con_informe, totalizar, unir_continuaciones,
+    version, ColumnaPdf, Confianza, Config, DatosPagina, EstrategiaMonto, Evento, Idioma, Informe,
+    OpcionesExcel, Verbosidad, EXTENSIONES_IMAGEN, TOLERANCIA_MONTO,
+};
 use regex::Regex;
 use rfd::FileDialog;
-use rust_xlsxwriter::{Formula, Table, TableColumn, TableStyle, Workbook};
-use std::collections::HashSet;
-use std::path::PathBuf;
-
-/// Datos extraídos de una página del PDF
-#[derive(Debug, Clone)]
-struct DatosPagina {
-    nombre: String,
-    expediente: String,
-    año: String,
-    monto: String,
-    cheque: String,
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::env;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Formato del archivo de salida
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+enum Formato {
+    #[default]
+    Xlsx,
+    Ods,
+    Json,
+    /// JSON con claves ASCII y valores de tipo fijo (`--flat`)
+    JsonPlano,
+    Csv,
 }
 
-/// Extrae el texto entre comillas dobles que sigue a la palabra "autos".
-fn extraer_texto_entre_comillas(texto: &str, p: usize) -> String {
-    let patron = Regex::new(r#"autos\s+"(.*?)""#).unwrap();
-    let eliminar_palabras: HashSet<&str> = ["ut-supra", "ut -supra"].iter().cloned().collect();
+impl Formato {
+    fn desde_nombre(nombre: &str) -> Option<Formato> {
+        match nombre.to_lowercase().as_str() {
+            "xlsx" | "excel" => Some(Formato::Xlsx),
+            "ods" => Some(Formato::Ods),
+            "json" => Some(Formato::Json),
+            "json-flat" | "json-plano" => Some(Formato::JsonPlano),
+            "csv" => Some(Formato::Csv),
+            _ => None,
+        }
+    }
 
-    let coincidencias: Vec<&str> = patron
-        .captures_iter(texto)
-        .filter_map(|c| c.get(1).map(|m| m.as_str()))
-        .filter(|s| !eliminar_palabras.contains(*s))
-        .collect();
+    fn extension(self) -> &'static str {
+        match self {
+            Formato::Xlsx => "xlsx",
+            Formato::Ods => "ods",
+            Formato::Json | Formato::JsonPlano => "json",
+            Formato::Csv => "csv",
+        }
+    }
 
-    if coincidencias.is_empty() {
-        (p + 1).to_string()
-    } else {
-        coincidencias[0].to_string()
+    fn descripcion(self) -> &'static str {
+        match self {
+            Formato::Xlsx => "Excel",
+            Formato::Ods => "ODS",
+            Formato::Json | Formato::JsonPlano => "JSON",
+            Formato::Csv => "CSV",
+        }
     }
 }
 
-/// Extrae el número de expediente y el año del texto.
-fn extraer_expediente_y_año(texto: &str, p: usize) -> (String, String) {
-    // Normalizar texto
-    let texto = Regex::new(r"(?i)expediente")
-        .unwrap()
-        .replace_all(texto, "EXP-")
-        .to_string();
-    let texto = Regex::new(r"(?i)Expte\.")
-        .unwrap()
-        .replace_all(&texto, "EXP-")
-        .to_string();
-
-    // Patrones sin lookbehind (incompatible con regex de Rust)
-    let patrones_expediente = [
-        r"[Ee][Xx][Pp]\-[^,]*,",
-        r"[Ee][Xx][Pp]\.[^,]*,",
-        r"[Ee][Xx][Pp] [^,]*,",
-        r"\d{4,6}-\d{4}",
-        r"\d{4,6}/\d{4}",
-        r"[Ee][Jj][Ff]\-[^,]*,",
-    ];
-
-    let mut expediente: Option<String> = None;
-    let mut patron_usado = "";
-
-    for patron_str in &patrones_expediente {
-        if let Ok(patron) = Regex::new(patron_str) {
-            if let Some(m) = patron.find(&texto) {
-                expediente = Some(m.as_str().to_uppercase().replace(' ', ""));
-                patron_usado = patron_str;
-                break;
+/// Opciones recibidas por línea de comandos
+#[derive(Debug, Default)]
+struct Opciones {
+    entradas: Vec<PathBuf>,
+    lista_entradas: Option<PathBuf>,
+    salida: Option<PathBuf>,
+    directorio_salida: Option<PathBuf>,
+    /// Con `directorio_salida`, un archivo por expediente en lugar de uno por PDF
+    dividir_por_expediente: bool,
+    formato: Formato,
+    config: Config,
+    excel: OpcionesExcel,
+    simulacion: bool,
+    registro: Option<PathBuf>,
+    errores_json: Option<PathBuf>,
+    estadisticas: bool,
+    unir_continuaciones: bool,
+    filtro_expediente: Option<Regex>,
+    estricto: bool,
+    /// Hilos para procesar las páginas en paralelo; `None` usa uno por CPU lógica
+    hilos: Option<usize>,
+    revision: bool,
+    patrones: Option<PathBuf>,
+    montos_esperados: Option<PathBuf>,
+    verbosidad: Verbosidad,
+}
+
+const AYUDA: &str =
+    "Uso: pdf-procuracion [ARCHIVO.pdf|DIRECTORIO...] [--output ARCHIVO] [opciones]
+
+Opciones:
+  -i, --input <PDF>         Archivo PDF a procesar, directorio con varios PDF o - (stdin);
+                            también acepta imágenes TIFF, PNG o JPEG, que se leen con OCR;
+                            se puede repetir o pasar las rutas sin -i y se unen en una salida
+      --input-list <ARCHIVO> Lista de PDF a procesar, uno por línea (# para comentarios);
+                            las rutas relativas se toman desde el directorio de la lista
+  -o, --output <ARCHIVO>    Archivo de salida
+      --output-dir <DIR>    Escribe un archivo de salida por cada PDF en DIR (caso1.pdf → caso1.xlsx)
+      --split-by-expediente Con --output-dir, escribe un archivo por expediente en lugar de uno
+                            por PDF (EXP-12345.xlsx; sin_expediente.xlsx para los no encontrados)
+  -f, --format <FORMATO>    Formato de salida: xlsx (por defecto), ods, json, json-flat o csv;
+                            una salida -o terminada en .ods se escribe como ODS
+      --flat                Salida JSON plana (igual que -f json-flat): claves ASCII, monto y
+                            año numéricos y null en los campos no encontrados (para pandas y
+                            otras herramientas); no se combina con otro formato
+  -a, --append              Agrega los registros debajo de la hoja PDF existente, salteando
+                            los que ya están (mismo cheque, monto y expediente)
+      --no-dedup            Con --append, agrega también los registros que ya están
+      --sin-rend            Escribe solo la hoja PDF, sin la hoja REND ni sus fórmulas
+      --cruce-aproximado    Cruza las filas de REND sin coincidencia exacta comparando cheque o
+                            expediente sin espacios, mayúsculas ni prefijos (CH, EXP-); anota
+                            la fila de la hoja PDF con que se cruzó
+      --start-row <N>       Fila de los encabezados de la hoja PDF (por defecto 1); las filas
+                            de arriba quedan para el título de la plantilla y se conservan
+      --columnas <LISTA>    Orden de las columnas de la hoja PDF: cheque,monto,nombre...
+      --lang <IDIOMA>       Idioma de los encabezados del Excel: es (por defecto) o en
+      --hoja <NOMBRE>       Hoja del Excel existente con la rendición (por defecto REND)
+      --estilo-tabla <EST>  Estilo de las tablas del Excel: Light1 (por defecto), Medium9...
+  -p, --password <CLAVE>    Contraseña del PDF si está encriptado
+      --anio-min <AÑO>      Primer año de expediente aceptado (por defecto 1990)
+      --anio-max <AÑO>      Último año de expediente aceptado (por defecto el actual)
+      --patrones <ARCHIVO>  Archivo TOML o JSON con patrones de expediente y reglas de prefijo
+      --clave-nombre <PAL>  Palabra adicional a autos que precede al nombre entre comillas
+      --filter-expediente <REGEX> Conserva solo los registros cuyo expediente coincide
+      --montos-esperados <CSV> Cheques y montos oficiales; anota los montos que no coinciden
+      --estrategia-monto <E> Monto a tomar si la página tiene varios: primero (por defecto),
+                            mayor o ultimo
+      --round <N>           Redondea los montos a N decimales (a par en los empates: 0,125 → 0,12)
+      --max-nombre <N>      Recorta los nombres de más de N caracteres (el completo va a Observaciones)
+      --min-chars <N>       Mínimo de caracteres para procesar una página (por defecto 500)
+      --columnas-texto <N>  Lee el texto por posición, en N columnas de igual ancho (documentos
+                            a dos columnas: 2)
+      --omitir-sin-datos    Omite las páginas sin monto ($...) ni expediente (carátulas, índices)
+      --cheque-digitos <N>  Dígitos del número de cheque (por defecto 8)
+      --pages <PÁGINAS>     Procesa solo esas páginas: 10-25, 10,12,30 o 1-3,7
+      --min-pages <N>       Rechaza los PDF con menos de N páginas
+      --max-pages <N>       Rechaza los PDF con más de N páginas
+      --sample <N>          Deja de procesar cada PDF cuando N páginas produjeron registros,
+                            para probar los patrones en un formato nuevo (salida parcial)
+      --cache               Guarda el texto extraído en el caché del usuario (carpeta
+                            pdf-procuracion) y lo reutiliza con los PDF que no cambiaron
+      --dump-text <DIR>     Guarda el texto limpio de cada página en DIR/page_<n>.txt
+      --log <ARCHIVO>       Guarda las páginas omitidas o con error y un resumen
+      --error-json <ARCHIVO> Guarda las páginas omitidas o con error como JSON (archivo,
+                            pagina, motivo, mensaje) para procesarlas con otros programas
+      --stats               Muestra al final las páginas procesadas, los campos no encontrados
+                            y cuántos expedientes encontró cada patrón
+      --review              Muestra cada registro para aceptarlo, corregirlo o descartarlo
+      --strict              No escribe la salida si algún campo quedó sin encontrar y lista
+                            las páginas afectadas
+      --dry-run             Muestra los datos extraídos sin escribir ningún archivo
+      --blank-on-miss       Deja vacíos los campos no encontrados en vez del número de página
+      --expediente-relajado Si no se encuentra el expediente, toma cualquier número 1234/20
+      --plegar-acentos      Quita los acentos del texto antes de extraer (é → e)
+      --unir-continuaciones Une el registro sin cheque con el de la página siguiente sin monto
+                            si tienen el mismo expediente
+      --todas-las-caratulas Genera un registro por cada carátula de la página
+      --todos-los-cheques   Genera un registro por cada cheque de la página
+      --validar-cheques     Anota en Observaciones los cheques en cero o con muy pocos dígitos
+      --ocr                 Lee con OCR (tesseract) las páginas escaneadas sin texto
+      --threads <N>         Procesa las páginas con N hilos (por defecto, uno por CPU)
+  -q, --quiet               No muestra la barra de progreso ni mensajes por página
+  -v, --verbose             Muestra una línea por cada página, procesada u omitida (por
+                            defecto solo se muestran los errores y el resumen)
+  -h, --help                Muestra esta ayuda
+  -V, --version             Muestra la versión y el commit con que se compiló
+
+Si falta alguna de las rutas se abre el diálogo de selección correspondiente.";
+
+/// Interpreta los argumentos de línea de comandos.
+///
+/// Devuelve `Ok(None)` si se pidió la ayuda.
+fn parsear_argumentos<I>(args: I) -> Result<Option<Opciones>, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut opciones = Opciones::default();
+    let mut args = args.into_iter();
+    // El formato pedido con -f y --flat se combinan al final, sin importar el orden
+    let mut formato = None;
+    let mut plano = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-i" | "--input" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                opciones.entradas.push(PathBuf::from(valor));
+            }
+            "--error-json" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                opciones.errores_json = Some(PathBuf::from(valor));
+            }
+            "--input-list" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                opciones.lista_entradas = Some(PathBuf::from(valor));
+            }
+            "-o" | "--output" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                opciones.salida = Some(PathBuf::from(valor));
+            }
+            "--output-dir" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                opciones.directorio_salida = Some(PathBuf::from(valor));
+            }
+            "--split-by-expediente" => opciones.dividir_por_expediente = true,
+            "-f" | "--format" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                formato = Some(
+                    Formato::desde_nombre(&valor)
+                        .ok_or(format!("Formato desconocido: {}", valor))?,
+                );
+            }
+            "--flat" => plano = true,
+            "-p" | "--password" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                opciones.config.contraseña = Some(valor);
+            }
+            "--anio-min" | "--anio-max" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                let año = valor
+                    .parse::<i32>()
+                    .map_err(|_| format!("Año inválido para {}: {}", arg, valor))?;
+                if arg == "--anio-min" {
+                    opciones.config.año_min = año;
+                } else {
+                    opciones.config.año_max = año;
+                }
+            }
+            "-a" | "--append" => opciones.excel.anexar = true,
+            "--no-dedup" => opciones.excel.sin_deduplicar = true,
+            "--sin-rend" => opciones.excel.sin_rend = true,
+            "--cruce-aproximado" => opciones.excel.cruce_aproximado = true,
+            "--start-row" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                let fila: u32 = valor
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or(format!("Valor inválido para {}: {}", arg, valor))?;
+                opciones.excel.fila_inicial = fila - 1;
+            }
+            "--columnas" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                let mut columnas = Vec::new();
+                for nombre in valor.split(',').filter(|n| !n.trim().is_empty()) {
+                    let columna = ColumnaPdf::desde_nombre(nombre)
+                        .ok_or(format!("Columna desconocida: {}", nombre))?;
+                    if columnas.contains(&columna) {
+                        return Err(format!("Columna repetida: {}", nombre));
+                    }
+                    columnas.push(columna);
+                }
+                opciones.excel.orden_columnas = Some(columnas);
+            }
+            "--lang" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                opciones.excel.idioma = Idioma::desde_nombre(&valor)
+                    .ok_or(format!("Idioma desconocido: {} (use es o en)", valor))?;
+            }
+            "--hoja" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                opciones.excel.hoja_origen = Some(valor);
+            }
+            "--estilo-tabla" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                estilo_tabla(&valor).ok_or(format!(
+                    "Estilo de tabla desconocido: {} (use Light1 a Light21, Medium1 a Medium28, \
+                     Dark1 a Dark11 o None)",
+                    valor
+                ))?;
+                opciones.excel.estilo_tabla = Some(valor);
+            }
+            "--clave-nombre" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                opciones
+                    .config
+                    .agregar_clave_nombre(&valor)
+                    .map_err(|e| e.to_string())?;
+            }
+            "--patrones" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                opciones.patrones = Some(PathBuf::from(valor));
+            }
+            "--filter-expediente" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                let patron =
+                    Regex::new(&valor).map_err(|e| format!("Patrón inválido en {}: {}", arg, e))?;
+                opciones.filtro_expediente = Some(patron);
+            }
+            "--montos-esperados" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                opciones.montos_esperados = Some(PathBuf::from(valor));
+            }
+            "--max-nombre" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                let largo = valor
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or(format!("Valor inválido para {}: {}", arg, valor))?;
+                opciones.config.largo_max_nombre = Some(largo);
             }
+            "--min-chars" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                opciones.config.min_caracteres = valor
+                    .parse()
+                    .map_err(|_| format!("Valor inválido para {}: {}", arg, valor))?;
+            }
+            "--sample" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                opciones.config.muestra = match valor.parse() {
+                    Ok(muestra) if muestra > 0 => Some(muestra),
+                    _ => return Err(format!("Valor inválido para {}: {}", arg, valor)),
+                };
+            }
+            "--threads" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                opciones.hilos = match valor.parse() {
+                    Ok(hilos) if hilos > 0 => Some(hilos),
+                    _ => return Err(format!("Valor inválido para {}: {}", arg, valor)),
+                };
+            }
+            "--columnas-texto" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                opciones.config.columnas_texto = match valor.parse() {
+                    Ok(columnas) if columnas > 0 => columnas,
+                    _ => return Err(format!("Valor inválido para {}: {}", arg, valor)),
+                };
+            }
+            "--min-pages" | "--max-pages" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                let paginas: usize = valor
+                    .parse()
+                    .map_err(|_| format!("Valor inválido para {}: {}", arg, valor))?;
+                if arg == "--min-pages" {
+                    opciones.config.min_paginas = paginas;
+                } else {
+                    opciones.config.max_paginas = Some(paginas);
+                }
+            }
+            "--cheque-digitos" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                opciones.config.longitud_cheque = valor
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or(format!("Valor inválido para {}: {}", arg, valor))?;
+            }
+            "--pages" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                let paginas = parsear_paginas(&valor).map_err(|e| e.to_string())?;
+                opciones.config.paginas = Some(paginas);
+            }
+            "--cache" => opciones.config.cache_texto = Some(directorio_cache()),
+            "--dump-text" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                opciones.config.volcado_texto = Some(PathBuf::from(valor));
+            }
+            "--log" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                opciones.registro = Some(PathBuf::from(valor));
+            }
+            "--strict" => opciones.estricto = true,
+            "--dry-run" => opciones.simulacion = true,
+            "--review" => opciones.revision = true,
+            "--stats" => opciones.estadisticas = true,
+            "--unir-continuaciones" => opciones.unir_continuaciones = true,
+            "--expediente-relajado" => opciones.config.expediente_relajado = true,
+            "--plegar-acentos" => opciones.config.plegar_acentos = true,
+            "--blank-on-miss" => opciones.config.vacio_si_falta = true,
+            "--estrategia-monto" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                opciones.config.estrategia_monto = EstrategiaMonto::desde_nombre(&valor)
+                    .ok_or(format!("Estrategia de monto desconocida: {}", valor))?;
+            }
+            "--round" => {
+                let valor = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+                opciones.config.decimales_monto = Some(
+                    valor
+                        .parse()
+                        .map_err(|_| format!("Valor inválido para {}: {}", arg, valor))?,
+                );
+            }
+            "--todas-las-caratulas" => opciones.config.todas_las_caratulas = true,
+            "--omitir-sin-datos" => opciones.config.omitir_sin_datos = true,
+            "--todos-los-cheques" => opciones.config.todos_los_cheques = true,
+            "--validar-cheques" => opciones.config.validar_cheques = true,
+            "--ocr" if cfg!(feature = "ocr") => opciones.config.ocr = true,
+            "--ocr" => return Err("Esta versión se compiló sin soporte de OCR".to_string()),
+            "-q" | "--quiet" => opciones.verbosidad = Verbosidad::Silencioso,
+            "-v" | "--verbose" => opciones.verbosidad = Verbosidad::Detallado,
+            "-h" | "--help" => return Ok(None),
+            "-V" | "--version" => {
+                println!("pdf-procuracion {}", version());
+                std::process::exit(0);
+            }
+            _ if arg == "-" || !arg.starts_with('-') => opciones.entradas.push(PathBuf::from(arg)),
+            _ => return Err(format!("Argumento desconocido: {}", arg)),
         }
     }
 
-    let mut expediente = match expediente {
-        Some(e) => e,
-        None => return ((p + 1).to_string(), " ".to_string()),
+    opciones.formato = match (formato, plano) {
+        (None, false) => opciones.formato,
+        (Some(formato), false) => formato,
+        (None | Some(Formato::Json | Formato::JsonPlano), true) => Formato::JsonPlano,
+        (Some(formato), true) => {
+            return Err(format!(
+                "--flat solo se puede usar con la salida JSON, no con {}",
+                formato.descripcion()
+            ))
+        }
     };
-
-    // Para el caso de EXP #### y no EXP-####
-    if patron_usado == r"[Ee][Xx][Pp] [^,]*," {
-        expediente = expediente.replace("EXP", "");
+    if opciones.revision && opciones.entradas.iter().any(|e| e == Path::new("-")) {
+        return Err("No se puede usar --review leyendo el PDF de la entrada estándar".to_string());
     }
-
-    // Acortar si es muy largo (usar chars para UTF-8 safety)
-    let chars: Vec<char> = expediente.chars().collect();
-    if chars.len() > 30 {
-        expediente = chars[..25].iter().collect();
+    if opciones.salida.is_some() && opciones.directorio_salida.is_some() {
+        return Err("No se pueden usar --output y --output-dir a la vez".to_string());
     }
+    if opciones.dividir_por_expediente && opciones.directorio_salida.is_none() {
+        return Err("--split-by-expediente necesita --output-dir DIR".to_string());
+    }
+
+    Ok(Some(opciones))
+}
+
+/// Directorio del caché del texto extraído: el de caché del usuario según el
+/// sistema, o el temporal si no se puede determinar.
+fn directorio_cache() -> PathBuf {
+    let base = env::var_os("LOCALAPPDATA")
+        .or_else(|| env::var_os("XDG_CACHE_HOME"))
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .unwrap_or_else(env::temp_dir);
+    base.join("pdf-procuracion")
+}
 
-    // Buscar el último dígito
-    let chars: Vec<char> = expediente.chars().collect();
-    let mut ultimo_digito = chars.len() as i32 - 1;
-    while ultimo_digito >= 0 && !chars[ultimo_digito as usize].is_ascii_digit() {
-        ultimo_digito -= 1;
+/// Lee una lista de entradas: una ruta por línea, sin las líneas vacías ni las
+/// que empiezan con `#`. Las rutas relativas se resuelven desde el directorio
+/// de la lista, para que la lista funcione desde cualquier directorio.
+fn leer_lista_entradas(lista: &Path) -> io::Result<Vec<PathBuf>> {
+    let base = lista.parent().unwrap_or(Path::new(""));
+    let contenido = std::fs::read_to_string(lista)?;
+    Ok(contenido
+        .lines()
+        .map(str::trim)
+        .filter(|linea| !linea.is_empty() && !linea.starts_with('#'))
+        .map(|linea| base.join(linea))
+        .collect())
+}
+
+/// Procesa una entrada: un PDF o imagen, un directorio o `-` (la entrada estándar).
+fn procesar_entrada(
+    pdf_path: &Path,
+    config: &Config,
+    informe: &mut Informe,
+) -> Result<Vec<DatosPagina>, String> {
+    let resultado = if pdf_path == Path::new("-") {
+        let mut contenido = Vec::new();
+        io::stdin()
+            .read_to_end(&mut contenido)
+            .map_err(|e| format!("No se pudo leer el PDF de la entrada estándar: {}", e))?;
+        procesar_pdf_mem_con_informe(&contenido, "stdin", config, informe)
+    } else if pdf_path.is_dir() {
+        procesar_directorio_con_informe(pdf_path, config, informe)
+    } else {
+        procesar_pdf_con_informe(pdf_path, config, informe)
+    };
+    resultado.map_err(|e| e.to_string())
+}
+
+/// Indica si se pueden abrir los diálogos de selección de archivos.
+///
+/// En Linux y los BSD los diálogos necesitan un servidor X11 o Wayland; sin
+/// ninguno (un servidor sin pantalla, una sesión SSH) fallan o quedan colgados.
+/// En Windows y macOS siempre hay entorno gráfico.
+fn hay_entorno_grafico() -> bool {
+    if cfg!(any(windows, target_os = "macos")) {
+        return true;
     }
-    if ultimo_digito >= 0 {
-        expediente = chars[..=(ultimo_digito as usize)].iter().collect();
+    ["DISPLAY", "WAYLAND_DISPLAY"]
+        .iter()
+        .any(|variable| env::var_os(variable).is_some_and(|valor| !valor.is_empty()))
+}
+
+/// Abre el diálogo para elegir el PDF a procesar.
+fn seleccionar_pdf() -> Option<PathBuf> {
+    println!("Seleccione el archivo PDF a procesar...");
+    FileDialog::new()
+        .add_filter("Archivos PDF", &["pdf"])
+        .add_filter("Imágenes escaneadas", &EXTENSIONES_IMAGEN)
+        .set_title("Seleccionar archivo PDF")
+        .pick_file()
+}
+
+/// Abre el diálogo para elegir el archivo de salida.
+fn seleccionar_salida(formato: Formato) -> Option<PathBuf> {
+    let descripcion = formato.descripcion();
+    println!("\nSeleccione el archivo {} de salida...", descripcion);
+    FileDialog::new()
+        .add_filter(format!("Archivos {}", descripcion), &[formato.extension()])
+        .set_title(format!("Seleccionar archivo {}", descripcion))
+        .pick_file()
+}
+
+/// Acorta un texto a `ancho` caracteres para mostrarlo en la tabla.
+fn recortar(texto: &str, ancho: usize) -> String {
+    if texto.chars().count() > ancho {
+        let mut recortado: String = texto.chars().take(ancho - 1).collect();
+        recortado.push('…');
+        recortado
+    } else {
+        texto.to_string()
     }
+}
 
-    // Extraer año (usar chars para UTF-8 safety)
-    let mut año = " ".to_string();
-    let chars: Vec<char> = expediente.chars().collect();
-    if chars.len() >= 4 {
-        let año_str: String = chars[chars.len() - 4..].iter().collect();
-        if let Ok(año_num) = año_str.parse::<i32>() {
-            if (1990..=2025).contains(&año_num) {
-                año = año_num.to_string();
-                if chars.len() >= 5 {
-                    expediente = chars[..chars.len() - 5].iter().collect();
-                }
-            }
+/// Imprime los registros extraídos como una tabla de texto.
+fn imprimir_tabla(datos: &[DatosPagina]) {
+    const ANCHO_MAX: usize = 40;
+    let encabezados = ["Pág.", "Nombre", "Expediente", "Año", "Monto", "Cheque"];
+
+    let filas: Vec<[String; 6]> = datos
+        .iter()
+        .map(|d| {
+            [
+                d.pagina.to_string(),
+                recortar(&d.nombre, ANCHO_MAX),
+                recortar(&d.expediente, ANCHO_MAX),
+                d.año.trim().to_string(),
+                d.monto.clone(),
+                d.cheque.clone(),
+            ]
+        })
+        .collect();
+
+    let mut anchos = encabezados.map(|e| e.chars().count());
+    for fila in &filas {
+        for (ancho, celda) in anchos.iter_mut().zip(fila) {
+            *ancho = (*ancho).max(celda.chars().count());
         }
     }
 
-    // Limpiar expediente
-    expediente = expediente
-        .replace("EXP. Nro.", "EXP-")
-        .replace("Nº", "")
-        .replace("N°", "")
-        .replace("EXP.", "EXP-")
-        .replace("NRO.", "");
+    let imprimir_fila = |celdas: &[&str]| {
+        let linea: Vec<String> = celdas
+            .iter()
+            .zip(&anchos)
+            .map(|(celda, &ancho)| {
+                let relleno = ancho - celda.chars().count();
+                format!("{}{}", celda, " ".repeat(relleno))
+            })
+            .collect();
+        println!("{}", linea.join(" | ").trim_end());
+    };
 
-    if expediente.ends_with('-') {
-        expediente.pop();
+    println!();
+    imprimir_fila(&encabezados);
+    let separador: Vec<String> = anchos.iter().map(|&a| "-".repeat(a)).collect();
+    println!("{}", separador.join("-+-"));
+    for fila in &filas {
+        imprimir_fila(&fila.each_ref().map(|c| c.as_str()));
     }
+}
 
-    if expediente.matches("EXP-").count() > 1 {
-        expediente = expediente.replacen("EXP-", "", 1);
-    } else if !expediente.contains("EXP-") && !expediente.contains("EJF-") {
-        expediente = format!("EXP-{}", expediente);
+/// Nombre de archivo para los registros de un expediente: el expediente con
+/// guiones bajos en lugar de los caracteres que no van en un nombre de archivo
+/// ("EXP-123/20" → "EXP-123_20"), o "sin_expediente" si no se encontró.
+fn nombre_por_expediente(expediente: &str) -> String {
+    if expediente.is_empty() {
+        return "sin_expediente".to_string();
     }
-
-    (expediente, año)
+    expediente
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
 }
 
-/// Extrae el monto del texto.
-fn extraer_monto(texto: &str, p: usize) -> String {
-    let texto = texto.replace("( $", "($");
+/// Agrupa los registros por PDF de origen, o por expediente si `por_expediente`,
+/// y arma la ruta de salida de cada grupo dentro de `directorio`, con el nombre
+/// del PDF (o del expediente) y la extensión del formato.
+///
+/// Los registros de un expediente se agrupan por el expediente ya limpio; los que
+/// no lo tienen (número de página de respaldo) van todos al mismo archivo.
+/// Devuelve un error con la lista de colisiones si dos grupos darían el mismo
+/// archivo de salida (por ejemplo `caso1.pdf` y `caso1.PDF`).
+fn salidas_por_archivo(
+    datos: Vec<DatosPagina>,
+    directorio: &Path,
+    formato: Formato,
+    por_expediente: bool,
+) -> Result<Vec<(PathBuf, Vec<DatosPagina>)>, String> {
+    let mut grupos: BTreeMap<String, Vec<DatosPagina>> = BTreeMap::new();
+    for dato in datos {
+        let clave = if !por_expediente {
+            dato.archivo.clone()
+        } else if dato.confianza.expediente == Confianza::Respaldo {
+            String::new()
+        } else {
+            dato.expediente.clone()
+        };
+        grupos.entry(clave).or_default().push(dato);
+    }
 
-    // Buscar patrón ($...) sin lookbehind
-    let patron = Regex::new(r"\(\$([^)]+)\)").unwrap();
-    let coincidencia = match patron.captures(&texto) {
-        Some(c) => c.get(1).map(|m| m.as_str()).unwrap_or(""),
-        None => return (p + 1).to_string(),
-    };
+    let mut origenes: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut salidas = Vec::new();
+    for (clave, datos) in grupos {
+        let nombre = if por_expediente {
+            nombre_por_expediente(&clave)
+        } else {
+            Path::new(&clave)
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_else(|| clave.clone())
+        };
+        let nombre = format!("{}.{}", nombre, formato.extension());
+        origenes
+            .entry(nombre.to_lowercase())
+            .or_default()
+            .push(clave);
+        salidas.push((directorio.join(nombre), datos));
+    }
 
-    let mut monto = coincidencia.replace('$', "").replace(' ', "");
-
-    // Limpiar terminaciones
-    if monto.ends_with(".-") {
-        monto = monto[..monto.len() - 2].to_string();
-    } else if monto.ends_with('.') {
-        monto = monto[..monto.len() - 1].to_string();
-    }
-
-    if monto.len() < 3 {
-        return monto;
-    }
-
-    let chars: Vec<char> = monto.chars().collect();
-    let len = chars.len();
-    let tercer_desde_final = chars.get(len.saturating_sub(3)).cloned().unwrap_or(' ');
-    let num_puntos = monto.matches('.').count();
-    let num_comas = monto.matches(',').count();
-
-    if tercer_desde_final == '.' && num_puntos > 1 {
-        // Caso: 1.234.567.89 -> 1234567.89
-        let parte_decimal = &monto[monto.len() - 2..];
-        let parte_entera = &monto[..monto.len() - 3];
-        monto = format!("{}.{}", parte_entera.replace('.', ""), parte_decimal);
-    } else if tercer_desde_final == '.' {
-        // Caso: 1,234.56 -> 1234.56
-        monto = monto.replace(',', "");
-    } else if tercer_desde_final == ',' && num_comas > 1 {
-        // Caso: 1,234,567,89 -> 1234567.89
-        let parte_decimal = &monto[monto.len() - 2..];
-        let parte_entera = &monto[..monto.len() - 3];
-        monto = format!("{}.{}", parte_entera.replace(',', ""), parte_decimal);
-    } else {
-        // Caso: 1.234.567 o 1,234,567 -> entero
-        monto = monto.replace('.', "").replace(',', ".");
+    let colisiones: Vec<String> = origenes
+        .into_iter()
+        .filter(|(_, archivos)| archivos.len() > 1)
+        .map(|(nombre, archivos)| format!("{} ← {}", nombre, archivos.join(", ")))
+        .collect();
+    if !colisiones.is_empty() {
+        return Err(format!(
+            "Varios {} generarían el mismo archivo de salida:\n  {}",
+            if por_expediente { "expedientes" } else { "PDF" },
+            colisiones.join("\n  ")
+        ));
     }
 
-    monto
+    Ok(salidas)
 }
 
-/// Extrae el número de cheque del texto.
-fn extraer_numero_cheque(texto: &str, p: usize) -> String {
-    let texto_limpio = texto.replace('.', "").replace('-', "").replace(' ', "");
-
-    // Buscar ChequeNro o ChequeN°
-    let patrones_cheque = [r"ChequeNro(\d+)", r"ChequeN°(\d+)"];
-    for patron_str in &patrones_cheque {
-        if let Ok(patron) = Regex::new(patron_str) {
-            if let Some(caps) = patron.captures(&texto_limpio) {
-                if let Some(m) = caps.get(1) {
-                    let numero_str = m.as_str();
-                    if numero_str.len() >= 8 {
-                        let numero_str = &numero_str[..8];
-                        if let Ok(numero) = numero_str.parse::<u64>() {
-                            return format!("CH {}", numero);
-                        }
-                    }
-                }
+/// Guarda los registros en `salida` con el formato elegido e informa el resultado.
+///
+/// Devuelve si se pudo guardar.
+fn guardar(datos: &[DatosPagina], salida: &Path, formato: Formato, excel: &OpcionesExcel) -> bool {
+    // La ruta elegida en el diálogo puede venir sin extensión
+    let salida = match formato {
+        Formato::Xlsx => match normalizar_salida_excel(salida) {
+            Ok(normalizada) => normalizada,
+            Err(e) => {
+                eprintln!("Error al guardar el archivo Excel: {}", e);
+                return false;
             }
+        },
+        _ => salida.to_path_buf(),
+    };
+    let salida = salida.as_path();
+    let resultado = match formato {
+        Formato::Xlsx => guardar_y_formatear_excel(datos, salida, excel),
+        Formato::Ods => guardar_ods(datos, salida, excel),
+        Formato::Json => guardar_json(datos, salida).map(|_| Vec::new()),
+        Formato::JsonPlano => guardar_json_plano(datos, salida).map(|_| Vec::new()),
+        Formato::Csv => guardar_csv(datos, salida).map(|_| Vec::new()),
+    };
+
+    for mensaje in resultado.iter().flatten() {
+        println!("{}", mensaje);
+    }
+    match resultado {
+        Ok(_) if formato == Formato::Xlsx => println!(
+            "\n✓ Archivo Excel guardado y formateado correctamente: {:?}",
+            salida
+        ),
+        Ok(_) => println!(
+            "\n✓ Archivo {} guardado correctamente: {:?}",
+            formato.descripcion(),
+            salida
+        ),
+        Err(e) => {
+            eprintln!(
+                "Error al guardar el archivo {}: {}",
+                formato.descripcion(),
+                e
+            );
+            return false;
         }
     }
+    true
+}
 
-    // Buscar ITBNº:
-    if let Ok(patron) = Regex::new(r"ITBNº:(\d+)") {
-        if let Some(caps) = patron.captures(&texto_limpio) {
-            if let Some(m) = caps.get(1) {
-                if let Ok(numero) = m.as_str().parse::<u64>() {
-                    return format!("ITB {}", numero);
-                }
+/// Guarda el informe de `--log` y el JSON de errores de `--error-json`, si se pidieron.
+///
+/// Devuelve si se pudieron guardar.
+fn guardar_informes(informe: &Informe, opciones: &Opciones) -> bool {
+    let mut guardados = true;
+    if let Some(registro) = &opciones.registro {
+        match informe.guardar(registro) {
+            Ok(_) => println!("Informe guardado en {:?}", registro),
+            Err(e) => {
+                eprintln!("Error al guardar el informe: {}", e);
+                guardados = false;
             }
         }
     }
-
-    // Buscar INTERNO:
-    if let Ok(patron) = Regex::new(r"INTERNO:(\d+)") {
-        if let Some(caps) = patron.captures(&texto_limpio) {
-            if let Some(m) = caps.get(1) {
-                let numero_str = m.as_str();
-                if numero_str.len() > 4 {
-                    let numero_str = &numero_str[..numero_str.len() - 4];
-                    if let Ok(numero) = numero_str.parse::<u64>() {
-                        if texto.contains("M.E.P.") {
-                            return format!("MEP {}", numero);
-                        }
-                        return format!("ITB {}", numero);
-                    }
-                }
-            }
+    if let Some(errores) = &opciones.errores_json {
+        if let Err(e) = informe.guardar_json(errores) {
+            eprintln!("Error al guardar el informe JSON: {}", e);
+            guardados = false;
         }
     }
+    guardados
+}
 
-    (p + 1).to_string()
+/// Código de salida del programa: 1 si algo falló, para que quien lo ejecuta
+/// desde un script se entere.
+fn codigo_salida(fallo: bool) -> ExitCode {
+    if fallo {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
 }
 
-/// Procesa un archivo PDF y extrae la información relevante de cada página.
-fn procesar_pdf(ruta_archivo: &PathBuf) -> Result<Vec<DatosPagina>, Box<dyn std::error::Error>> {
-    let doc = Document::load(ruta_archivo)?;
-    let mut lista_datos = Vec::new();
+/// Muestra un mensaje y lee una línea de la entrada estándar; `None` al terminar la entrada.
+fn leer_linea(mensaje: &str) -> Option<String> {
+    print!("{}", mensaje);
+    io::stdout().flush().ok()?;
+    let mut linea = String::new();
+    match io::stdin().read_line(&mut linea) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(linea.trim().to_string()),
+    }
+}
 
-    // Obtener todas las páginas del documento
-    let pages = doc.get_pages();
-    let num_pages = pages.len();
+/// Muestra los campos de un registro para revisarlo.
+fn mostrar_registro(numero: usize, total: usize, dato: &DatosPagina) {
+    println!(
+        "\nRegistro {} de {} ({}, pág. {})",
+        numero, total, dato.archivo, dato.pagina
+    );
+    println!("  nombre:        {}", dato.nombre);
+    println!("  expediente:    {}", dato.expediente);
+    println!("  año:           {}", dato.año.trim());
+    println!("  monto:         {} {}", dato.monto, dato.moneda);
+    println!("  cheque:        {}", dato.cheque);
+    println!("  observaciones: {}", dato.observaciones.join("; "));
+}
 
-    println!("El PDF tiene {} páginas", num_pages);
+/// Pide un campo y su nuevo valor, y lo corrige en el registro.
+///
+/// El valor corregido a mano se toma como encontrado, así no se resalta en el Excel
+/// y se quita su aviso de las observaciones.
+fn editar_registro(dato: &mut DatosPagina) {
+    let Some(campo) = leer_linea("Campo (nombre, expediente, año, monto, cheque, observaciones): ")
+    else {
+        return;
+    };
+    let campo = campo.to_lowercase();
+    if !matches!(
+        campo.as_str(),
+        "nombre" | "expediente" | "año" | "anio" | "monto" | "cheque" | "observaciones"
+    ) {
+        println!("Campo desconocido: {}", campo);
+        return;
+    }
+    let Some(valor) = leer_linea("Nuevo valor: ") else {
+        return;
+    };
 
-    // Iterar sobre cada página
-    for (p, (page_num, _page_id)) in pages.iter().enumerate() {
-        // Extraer texto de esta página específica
-        let texto_pagina = match doc.extract_text(&[*page_num]) {
-            Ok(t) => t,
-            Err(e) => {
-                println!("Error extrayendo texto de página {}: {}", page_num, e);
-                continue;
+    match campo.as_str() {
+        "nombre" => {
+            dato.nombre = valor;
+            dato.confianza.nombre = Confianza::Encontrado;
+        }
+        "expediente" => {
+            dato.expediente = valor;
+            dato.confianza.expediente = Confianza::Encontrado;
+        }
+        "año" | "anio" => {
+            dato.año = valor;
+            dato.confianza.año = Confianza::Encontrado;
+        }
+        "monto" => {
+            // Se acepta la coma decimal: "1.500,50" → "1500.50"
+            dato.monto = if valor.contains(',') {
+                valor.replace('.', "").replace(',', ".")
+            } else {
+                valor
+            };
+            if dato.moneda.is_empty() {
+                dato.moneda = MONEDA_PESOS.to_string();
             }
-        };
-
-        // Limpiar el texto
-        let texto: String = texto_pagina
-            .replace('\n', " ")
-            .chars()
-            .filter(|c| c.is_ascii() || c.is_alphanumeric() || c.is_whitespace())
-            .collect();
+            dato.confianza.monto = Confianza::Encontrado;
+        }
+        "cheque" => {
+            dato.cheque = valor;
+            dato.confianza.cheque = Confianza::Encontrado;
+        }
+        // Las observaciones escritas a mano reemplazan también a los avisos
+        "observaciones" => {
+            dato.observaciones = valor
+                .split(';')
+                .map(str::trim)
+                .filter(|o| !o.is_empty())
+                .map(str::to_string)
+                .collect();
+            return;
+        }
+        _ => {}
+    }
+    dato.actualizar_avisos();
+}
 
-        // Saltar páginas con menos de 500 caracteres
-        if texto.len() < 500 {
-            println!(
-                "Página {} omitida: solo {} caracteres",
-                page_num,
-                texto.len()
-            );
-            continue;
+/// Revisa los registros uno por uno antes de guardarlos.
+///
+/// Cada registro se puede aceptar, corregir o descartar; al terminar la entrada
+/// se aceptan los que faltan. Devuelve los registros aceptados.
+fn revisar(datos: Vec<DatosPagina>) -> Vec<DatosPagina> {
+    let total = datos.len();
+    let mut aceptados = Vec::with_capacity(total);
+    let mut pendientes = datos.into_iter().enumerate();
+
+    while let Some((i, mut dato)) = pendientes.next() {
+        loop {
+            mostrar_registro(i + 1, total, &dato);
+            let respuesta =
+                leer_linea("[a]ceptar, [e]ditar, [s]altar, [t]odos (aceptar el resto): ");
+            match respuesta.as_deref().map(str::to_lowercase).as_deref() {
+                Some("a" | "") => {
+                    aceptados.push(dato);
+                    break;
+                }
+                Some("s") => break,
+                Some("e") => editar_registro(&mut dato),
+                Some("t") | None => {
+                    aceptados.push(dato);
+                    aceptados.extend(pendientes.by_ref().map(|(_, d)| d));
+                    break;
+                }
+                Some(otra) => println!("Opción desconocida: {}", otra),
+            }
         }
+    }
+
+    println!("\nSe aceptaron {} de {} registros.", aceptados.len(), total);
+    aceptados
+}
 
+/// Imprime la suma de los montos por moneda y cuántos quedaron afuera.
+fn imprimir_totales(datos: &[DatosPagina]) {
+    let totales = totalizar(datos);
+    println!();
+    for (moneda, (total, cantidad)) in &totales.por_moneda {
+        println!("Total {}: {:.2} ({} montos)", moneda, total, cantidad);
+    }
+    if totales.sin_monto > 0 {
         println!(
-            "Procesando página {} ({} caracteres)",
-            page_num,
-            texto.len()
+            "Advertencia: {} registros sin monto no se sumaron",
+            totales.sin_monto
+        );
+    }
+    if totales.no_numericos > 0 {
+        println!(
+            "Advertencia: {} montos no numéricos no se sumaron",
+            totales.no_numericos
         );
-
-        let nombre = extraer_texto_entre_comillas(&texto, p);
-        let (expediente, año) = extraer_expediente_y_año(&texto, p);
-        let monto = extraer_monto(&texto, p);
-        let cheque = extraer_numero_cheque(&texto, p);
-
-        lista_datos.push(DatosPagina {
-            nombre,
-            expediente,
-            año,
-            monto,
-            cheque,
-        });
     }
-
-    Ok(lista_datos)
 }
 
-/// Guarda los datos en un archivo Excel y aplica formato.
-fn guardar_y_formatear_excel(
-    datos: &[DatosPagina],
-    output_path: &PathBuf,
-) -> Result<(), Box<dyn std::error::Error>> {
-    // Leer datos existentes de la hoja REND si existe
-    let datos_rend: Vec<Vec<String>> = if output_path.exists() {
-        let mut workbook: Xlsx<_> = open_workbook(output_path)?;
-        if let Ok(range) = workbook.worksheet_range("REND") {
-            range
-                .rows()
-                .skip(1) // Skip header
-                .map(|row| row.iter().map(|cell| cell.to_string()).collect())
-                .collect()
-        } else if let Some(Ok(range)) = workbook.worksheet_range_at(0) {
-            range
-                .rows()
-                .skip(1)
-                .map(|row| row.iter().map(|cell| cell.to_string()).collect())
-                .collect()
-        } else {
-            Vec::new()
+fn main() -> ExitCode {
+    let mut opciones = match parsear_argumentos(env::args().skip(1)) {
+        Ok(Some(o)) => o,
+        Ok(None) => {
+            println!("{}", AYUDA);
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, AYUDA);
+            return ExitCode::from(2);
         }
-    } else {
-        Vec::new()
     };
 
-    let mut workbook = Workbook::new();
-
-    // Crear hoja REND
-    let worksheet_rend = workbook.add_worksheet();
-    worksheet_rend.set_name("REND")?;
-
-    // Encabezados REND
-    let headers_rend = [
-        "Numero de Cheque",
-        "Monto",
-        "AUTOS",
-        "Expediente",
-        "Año",
-        "Observaciones",
-        "Control",
-        "Control cheque",
-    ];
-
-    for (col, header) in headers_rend.iter().enumerate() {
-        worksheet_rend.write_string(0, col as u16, *header)?;
-    }
-
-    // Escribir datos REND existentes
-    let mut max_row_rend = 0u32;
-    for (row_idx, row_data) in datos_rend.iter().enumerate() {
-        for (col_idx, cell) in row_data.iter().enumerate() {
-            if col_idx < 8 {
-                let row = (row_idx + 1) as u32;
-                let col = col_idx as u16;
-
-                // Columna B (índice 1) es Monto - escribir como número
-                if col_idx == 1 {
-                    // Convertir coma decimal a punto para parsear
-                    let monto_normalizado = cell.replace(',', ".");
-                    if let Ok(monto_num) = monto_normalizado.parse::<f64>() {
-                        worksheet_rend.write_number(row, col, monto_num)?;
-                    } else {
-                        worksheet_rend.write_string(row, col, cell)?;
-                    }
-                } else {
-                    worksheet_rend.write_string(row, col, cell)?;
-                }
+    if let Some(lista) = &opciones.lista_entradas {
+        match leer_lista_entradas(lista) {
+            Ok(entradas) => opciones.entradas.extend(entradas),
+            Err(e) => {
+                eprintln!("No se pudo leer la lista de entradas {:?}: {}", lista, e);
+                return ExitCode::from(2);
             }
         }
-        max_row_rend = (row_idx + 1) as u32;
     }
 
-    // Agregar fórmulas a REND (si hay datos)
-    if max_row_rend > 0 {
-        for row in 1..=max_row_rend {
-            // D: Expediente
-            let formula_d = format!(
-                "=IFERROR(INDEX(PDF!$B:$B,MATCH(A{0},PDF!$E:$E,0)),INDEX(PDF!$B:$B,MATCH(B{0},PDF!$D:$D,0)))",
-                row + 1
-            );
-            worksheet_rend.write_formula(row, 3, Formula::new(&formula_d))?;
-
-            // E: Año
-            let formula_e = format!(
-                "=IFERROR(IF(INDEX(PDF!$C:$C,MATCH(A{0},PDF!$E:$E,0))>0,INDEX(PDF!$C:$C,MATCH(A{0},PDF!$E:$E,0)),\"\"),IF(INDEX(PDF!$C:$C,MATCH(B{0},PDF!$D:$D,0))>0,INDEX(PDF!$C:$C,MATCH(B{0},PDF!$D:$D,0)),\"\"))",
-                row + 1
-            );
-            worksheet_rend.write_formula(row, 4, Formula::new(&formula_e))?;
-
-            // G: Control
-            let formula_g = format!("=COUNTIF(PDF!$D:$D,B{})", row + 1);
-            worksheet_rend.write_formula(row, 6, Formula::new(&formula_g))?;
-
-            // H: Control cheque
-            let formula_h = format!("=COUNTIF(PDF!$E:$E,A{})", row + 1);
-            worksheet_rend.write_formula(row, 7, Formula::new(&formula_h))?;
+    if let Some(patrones) = &opciones.patrones {
+        if let Err(e) = opciones.config.cargar_patrones(patrones) {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
         }
+    }
 
-        // Crear tabla REND
-        let table_rend = Table::new().set_style(TableStyle::Light1).set_columns(&[
-            TableColumn::new().set_header("Numero de Cheque"),
-            TableColumn::new().set_header("Monto"),
-            TableColumn::new().set_header("AUTOS"),
-            TableColumn::new().set_header("Expediente"),
-            TableColumn::new().set_header("Año"),
-            TableColumn::new().set_header("Observaciones"),
-            TableColumn::new().set_header("Control"),
-            TableColumn::new().set_header("Control cheque"),
-        ]);
-        worksheet_rend.add_table(0, 0, max_row_rend, 7, &table_rend)?;
-    }
-
-    // Crear hoja PDF
-    let worksheet_pdf = workbook.add_worksheet();
-    worksheet_pdf.set_name("PDF")?;
-
-    // Encabezados PDF
-    let headers_pdf = [
-        "Nombre",
-        "Expediente",
-        "año",
-        "Monto",
-        "Cheque",
-        "Control",
-        "Control cheque",
-    ];
-
-    for (col, header) in headers_pdf.iter().enumerate() {
-        worksheet_pdf.write_string(0, col as u16, *header)?;
-    }
-
-    // Escribir datos extraídos del PDF
-    for (row_idx, dato) in datos.iter().enumerate() {
-        let row = (row_idx + 1) as u32;
-
-        worksheet_pdf.write_string(row, 0, &dato.nombre)?;
-        worksheet_pdf.write_string(row, 1, &dato.expediente)?;
-
-        // Escribir año como número si es posible
-        if let Ok(año_num) = dato.año.trim().parse::<f64>() {
-            worksheet_pdf.write_number(row, 2, año_num)?;
-        } else {
-            worksheet_pdf.write_string(row, 2, &dato.año)?;
+    if let Some(hilos) = opciones.hilos {
+        if let Err(e) = rayon::ThreadPoolBuilder::new()
+            .num_threads(hilos)
+            .build_global()
+        {
+            eprintln!("No se pudo configurar el procesamiento en paralelo: {}", e);
+            return ExitCode::from(2);
         }
+    }
 
-        // Escribir monto como número si es posible
-        if let Ok(monto_num) = dato.monto.parse::<f64>() {
-            worksheet_pdf.write_number(row, 3, monto_num)?;
+    // Sin entorno gráfico los diálogos se cuelgan: avisar antes de procesar nada
+    let falta_salida =
+        !opciones.simulacion && opciones.salida.is_none() && opciones.directorio_salida.is_none();
+    if (opciones.entradas.is_empty() || falta_salida) && !hay_entorno_grafico() {
+        let falta = if opciones.entradas.is_empty() {
+            "el PDF con --input ARCHIVO.pdf"
         } else {
-            worksheet_pdf.write_string(row, 3, &dato.monto)?;
-        }
+            "la salida con --output ARCHIVO o --output-dir DIR"
+        };
+        eprintln!(
+            "No hay entorno gráfico para abrir el diálogo de selección; indique {}",
+            falta
+        );
+        return ExitCode::from(2);
+    }
 
-        worksheet_pdf.write_string(row, 4, &dato.cheque)?;
+    let montos_esperados = match &opciones.montos_esperados {
+        Some(path) => match cargar_montos_esperados(path) {
+            Ok(esperados) => Some(esperados),
+            Err(e) => {
+                eprintln!("{}", e);
+                return ExitCode::from(2);
+            }
+        },
+        None => None,
+    };
 
-        // Fórmulas de control
-        let formula_f = format!("=COUNTIF(REND!$B:$B,D{})", row + 1);
-        let formula_g = format!("=COUNTIF(REND!$A:$A,E{})", row + 1);
-        worksheet_pdf.write_formula(row, 5, Formula::new(&formula_f))?;
-        worksheet_pdf.write_formula(row, 6, Formula::new(&formula_g))?;
+    // Con el formato por defecto, la extensión .ods de la salida elige el formato ODS
+    let es_ods = |salida: &Path| {
+        salida
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("ods"))
+    };
+    if opciones.formato == Formato::Xlsx && opciones.salida.as_deref().is_some_and(es_ods) {
+        opciones.formato = Formato::Ods;
     }
 
-    // Crear tabla PDF
-    if !datos.is_empty() {
-        let max_row_pdf = datos.len() as u32;
-        let table_pdf = Table::new().set_style(TableStyle::Light1).set_columns(&[
-            TableColumn::new().set_header("Nombre"),
-            TableColumn::new().set_header("Expediente"),
-            TableColumn::new().set_header("año"),
-            TableColumn::new().set_header("Monto"),
-            TableColumn::new().set_header("Cheque"),
-            TableColumn::new().set_header("Control"),
-            TableColumn::new().set_header("Control cheque"),
-        ]);
-        worksheet_pdf.add_table(0, 0, max_row_pdf, 6, &table_pdf)?;
+    if let (Some(salida), Formato::Xlsx) = (&opciones.salida, opciones.formato) {
+        match normalizar_salida_excel(salida) {
+            Ok(normalizada) => opciones.salida = Some(normalizada),
+            Err(e) => {
+                eprintln!("{}", e);
+                return ExitCode::from(2);
+            }
+        }
     }
 
-    workbook.save(output_path)?;
-    Ok(())
-}
-
-fn main() {
     println!("PDF Procuración - Procesador de PDFs");
     println!("=====================================\n");
 
     // Seleccionar archivo PDF
-    println!("Seleccione el archivo PDF a procesar...");
-    let pdf_file = FileDialog::new()
-        .add_filter("Archivos PDF", &["pdf"])
-        .set_title("Seleccionar archivo PDF")
-        .pick_file();
+    let mut entradas = std::mem::take(&mut opciones.entradas);
+    if entradas.is_empty() {
+        match seleccionar_pdf() {
+            Some(path) => entradas.push(path),
+            None => {
+                println!("No se seleccionó ningún archivo PDF.");
+                return ExitCode::SUCCESS;
+            }
+        }
+    }
 
-    let pdf_path = match pdf_file {
-        Some(path) => path,
-        None => {
-            println!("No se seleccionó ningún archivo PDF.");
-            return;
+    let origen: Vec<String> = entradas.iter().map(|e| e.display().to_string()).collect();
+    opciones.excel.origen = Some(origen.join(", "));
+
+    // Procesar cada PDF (o todos los PDF del directorio) y unir los registros;
+    // con varias entradas, la que falla se informa sin cortar las demás
+    let mut informe = Informe::new(opciones.verbosidad);
+    let mut datos = Vec::new();
+    // Entrada de la que salió cada PDF, para el origen de las salidas por archivo
+    let mut entrada_de: HashMap<String, String> = HashMap::new();
+    for pdf_path in &entradas {
+        println!("Procesando: {:?}", pdf_path);
+        match procesar_entrada(pdf_path, &opciones.config, &mut informe) {
+            Ok(d) => {
+                for dato in &d {
+                    entrada_de
+                        .entry(dato.archivo.clone())
+                        .or_insert_with(|| pdf_path.display().to_string());
+                }
+                datos.extend(d);
+            }
+            Err(e) if entradas.len() == 1 => {
+                eprintln!("Error al procesar el PDF: {}", e);
+                // Quien lee el informe o el JSON de errores también tiene que enterarse
+                informe.eventos.push(Evento::ArchivoFallido {
+                    archivo: pdf_path.display().to_string(),
+                    mensaje: e,
+                });
+                guardar_informes(&informe, &opciones);
+                return ExitCode::FAILURE;
+            }
+            Err(e) => informe.registrar(Evento::ArchivoFallido {
+                archivo: pdf_path.display().to_string(),
+                mensaje: e,
+            }),
         }
-    };
+    }
 
-    println!("Procesando: {:?}", pdf_path);
+    // Las entradas que fallaron no cortan la corrida, pero sí el código de salida
+    let mut fallo = informe
+        .eventos
+        .iter()
+        .any(|e| matches!(e, Evento::ArchivoFallido { .. }));
 
-    // Procesar PDF
-    let datos = match procesar_pdf(&pdf_path) {
-        Ok(d) => d,
-        Err(e) => {
-            println!("Error al procesar el PDF: {}", e);
-            return;
-        }
-    };
+    println!("{}", informe.resumen());
+    if opciones.verbosidad == Verbosidad::Normal && informe.omitidas() > 0 {
+        println!("Las páginas omitidas se listan con --verbose o en el informe de --log");
+    }
+    fallo |= !guardar_informes(&informe, &opciones);
 
     println!("Se extrajeron {} registros del PDF.", datos.len());
+    if opciones.unir_continuaciones {
+        let uniones = unir_continuaciones(&mut datos);
+        if uniones > 0 {
+            println!(
+                "Se unieron {} registros partidos entre dos páginas.",
+                uniones
+            );
+        }
+    }
+    if let Some(patron) = &opciones.filtro_expediente {
+        let descartados = filtrar_por_expediente(&mut datos, patron);
+        println!(
+            "Se descartaron {} registros cuyo expediente no coincide con {}.",
+            descartados, patron
+        );
+    }
+    for duplicado in marcar_duplicados(&mut datos) {
+        let paginas: Vec<String> = duplicado
+            .indices
+            .iter()
+            .map(|&i| format!("{} pág. {}", datos[i].archivo, datos[i].pagina))
+            .collect();
+        println!(
+            "Advertencia: el cheque {} aparece {} veces ({})",
+            duplicado.cheque,
+            duplicado.indices.len(),
+            paginas.join(", ")
+        );
+    }
+    if let Some(esperados) = &montos_esperados {
+        let diferencias = cotejar_montos(&mut datos, esperados, TOLERANCIA_MONTO);
+        println!(
+            "Montos cotejados con la lista oficial: {} no coinciden",
+            diferencias
+        );
+    }
+    if opciones.estadisticas {
+        print!("\n{}", informe.estadisticas(&datos));
+    }
 
     if datos.is_empty() {
         println!("No se encontraron datos en el PDF.");
-        return;
+        return codigo_salida(fallo);
     }
 
-    // Seleccionar archivo Excel de salida
-    println!("\nSeleccione el archivo Excel de salida...");
-    let excel_file = FileDialog::new()
-        .add_filter("Archivos Excel", &["xlsx"])
-        .set_title("Seleccionar archivo Excel")
-        .pick_file();
+    if opciones.revision {
+        datos = revisar(datos);
+        if datos.is_empty() {
+            println!("No quedó ningún registro para guardar.");
+            return codigo_salida(fallo);
+        }
+    }
 
-    let excel_path = match excel_file {
+    // En modo estricto ningún campo puede quedar con el valor de respaldo
+    if opciones.estricto {
+        let incompletos: Vec<(&DatosPagina, Vec<&str>)> = datos
+            .iter()
+            .map(|dato| (dato, dato.confianza.respaldos()))
+            .filter(|(_, campos)| !campos.is_empty())
+            .collect();
+        if !incompletos.is_empty() {
+            eprintln!(
+                "Modo estricto: {} registros tienen campos sin encontrar; no se escribe ningún archivo.",
+                incompletos.len()
+            );
+            for (dato, campos) in &incompletos {
+                eprintln!(
+                    "  {} pág. {}: {}",
+                    dato.archivo,
+                    dato.pagina,
+                    campos.join(", ")
+                );
+            }
+            return ExitCode::FAILURE;
+        }
+    }
+
+    imprimir_totales(&datos);
+
+    if opciones.simulacion {
+        imprimir_tabla(&datos);
+        println!("\nSimulación: no se escribió ningún archivo.");
+        return codigo_salida(fallo);
+    }
+
+    let formato = opciones.formato;
+
+    // Un archivo de salida por PDF, o por expediente
+    if let Some(directorio) = &opciones.directorio_salida {
+        let por_expediente = opciones.dividir_por_expediente;
+        let salidas = match salidas_por_archivo(datos, directorio, formato, por_expediente) {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("{}", e);
+                return ExitCode::FAILURE;
+            }
+        };
+        if let Err(e) = std::fs::create_dir_all(directorio) {
+            eprintln!("No se pudo crear el directorio {:?}: {}", directorio, e);
+            return ExitCode::FAILURE;
+        }
+        for (salida, datos) in &salidas {
+            // Cada archivo nombra solo las entradas de sus registros
+            let mut vistas = HashSet::new();
+            let origen: Vec<&str> = datos
+                .iter()
+                .filter_map(|d| entrada_de.get(&d.archivo).map(String::as_str))
+                .filter(|entrada| vistas.insert(*entrada))
+                .collect();
+            let excel = OpcionesExcel {
+                origen: Some(origen.join(", ")),
+                ..opciones.excel.clone()
+            };
+            fallo |= !guardar(datos, salida, formato, &excel);
+        }
+        return codigo_salida(fallo);
+    }
+
+    // Seleccionar archivo de salida
+    let salida = match opciones.salida.or_else(|| seleccionar_salida(formato)) {
         Some(path) => path,
         None => {
-            println!("No se seleccionó ningún archivo Excel.");
-            return;
+            println!("No se seleccionó ningún archivo {}.", formato.descripcion());
+            return codigo_salida(fallo);
         }
     };
 
-    // Guardar y formatear Excel
-    match guardar_y_formatear_excel(&datos, &excel_path) {
-        Ok(_) => println!(
-            "\n✓ Archivo Excel guardado y formateado correctamente: {:?}",
-            excel_path
-        ),
-        Err(e) => println!("Error al guardar el archivo Excel: {}", e),
-    }
+    // Guardar en el formato elegido
+    fallo |= !guardar(&datos, &salida, formato, &opciones.excel);
+    codigo_salida(fallo)
 }
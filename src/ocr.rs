@@ -0,0 +1,103 @@
+//! Reconocimiento de texto (OCR) de páginas escaneadas.
+//!
+//! La página se rasteriza con `pdftoppm` (poppler) y la imagen se pasa a
+//! tesseract con los datos de idioma español. Requiere compilar con la
+//! feature `ocr` y tener ambas herramientas instaladas. Los TIFF de varias
+//! páginas se separan con `tiffsplit` (libtiff).
+
+use std::path::Path;
+use std::process::Command;
+
+/// Resolución con que se rasteriza la página
+const RESOLUCION_DPI: &str = "300";
+
+/// Idioma de tesseract
+#[cfg(feature = "ocr")]
+const IDIOMA: &str = "spa";
+
+/// Rasteriza la página `page_num` del PDF y devuelve el texto reconocido.
+pub(crate) fn reconocer_pagina(ruta: &Path, page_num: u32) -> Result<String, String> {
+    let base = std::env::temp_dir().join(format!(
+        "pdf-procuracion-{}-{}",
+        std::process::id(),
+        page_num
+    ));
+    let pagina = page_num.to_string();
+
+    let salida = Command::new("pdftoppm")
+        .args(["-f", &pagina, "-l", &pagina, "-r", RESOLUCION_DPI])
+        .args(["-png", "-singlefile"])
+        .arg(ruta)
+        .arg(&base)
+        .output()
+        .map_err(|e| format!("no se pudo ejecutar pdftoppm: {}", e))?;
+    if !salida.status.success() {
+        return Err(format!(
+            "pdftoppm falló: {}",
+            String::from_utf8_lossy(&salida.stderr).trim()
+        ));
+    }
+
+    let imagen = base.with_extension("png");
+    let texto = reconocer_imagen(&imagen);
+    let _ = std::fs::remove_file(&imagen);
+    texto
+}
+
+/// Reconoce el texto de cada página de una imagen: una sola para PNG o JPEG,
+/// o todas las de un TIFF de varias páginas.
+///
+/// El error externo indica que la imagen no pudo separarse en páginas; cada
+/// página tiene además su propio resultado.
+pub(crate) fn reconocer_paginas_imagen(ruta: &Path) -> Result<Vec<Result<String, String>>, String> {
+    let es_tiff = ruta
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("tif") || e.eq_ignore_ascii_case("tiff"));
+    if !es_tiff {
+        return Ok(vec![reconocer_imagen(ruta)]);
+    }
+
+    let directorio = std::env::temp_dir().join(format!("pdf-procuracion-{}", std::process::id()));
+    std::fs::create_dir_all(&directorio)
+        .map_err(|e| format!("no se pudo crear {:?}: {}", directorio, e))?;
+    let salida = Command::new("tiffsplit")
+        .arg(ruta)
+        .arg(directorio.join("pagina-"))
+        .output()
+        .map_err(|e| format!("no se pudo ejecutar tiffsplit: {}", e));
+    let paginas = salida.and_then(|salida| {
+        if !salida.status.success() {
+            return Err(format!(
+                "tiffsplit falló: {}",
+                String::from_utf8_lossy(&salida.stderr).trim()
+            ));
+        }
+        // tiffsplit nombra las páginas pagina-aaa.tif, pagina-aab.tif... en orden
+        let mut imagenes: Vec<_> = std::fs::read_dir(&directorio)
+            .map_err(|e| e.to_string())?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .collect();
+        imagenes.sort();
+        Ok(imagenes
+            .iter()
+            .map(|imagen| reconocer_imagen(imagen))
+            .collect())
+    });
+    let _ = std::fs::remove_dir_all(&directorio);
+    paginas
+}
+
+#[cfg(feature = "ocr")]
+fn reconocer_imagen(imagen: &Path) -> Result<String, String> {
+    let mut tesseract = leptess::LepTess::new(None, IDIOMA)
+        .map_err(|e| format!("no se pudo iniciar tesseract ({}): {}", IDIOMA, e))?;
+    tesseract
+        .set_image(imagen)
+        .map_err(|e| format!("no se pudo leer la imagen de la página: {}", e))?;
+    tesseract.get_utf8_text().map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "ocr"))]
+fn reconocer_imagen(_imagen: &Path) -> Result<String, String> {
+    Err("esta versión se compiló sin soporte de OCR".to_string())
+}
@@ -0,0 +1,617 @@
+//! Escritura de la planilla en formato OpenDocument (`.ods`), para LibreOffice.
+//!
+//! Tiene las mismas hojas REND y PDF que el archivo Excel, con las fórmulas de
+//! control escritas en la sintaxis de fórmulas de ODF
+//! (`of:=COUNTIF([$REND.$B$2:.$B$9];[.D2])`). No hay tablas ni estilos de tabla:
+//! solo el formato de número de los montos y las fechas. Los rangos de las
+//! fórmulas abarcan las filas escritas y cada fórmula lleva su resultado ya
+//! calculado, para que el archivo se vea bien aunque no se recalcule al abrirlo.
+//!
+//! El archivo (un ZIP con el XML del contenido) se arma aquí mismo.
+
+use crate::excel::{
+    self, monto_rend, ColumnaPdf, Disposicion, OpcionesExcel, Planilla, ETIQUETA_TOTAL, HOJA_REND,
+};
+use crate::extraccion::{MONEDA_DOLARES, MONEDA_PESOS};
+use crate::{detectar_duplicados, nombres_campos_extra, DatosPagina, ProcError};
+use chrono::NaiveDate;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+const MIME_ODS: &str = "application/vnd.oasis.opendocument.spreadsheet";
+
+/// Espacios de nombres de `content.xml`
+const NAMESPACES: &str = concat!(
+    r#"xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" "#,
+    r#"xmlns:style="urn:oasis:names:tc:opendocument:xmlns:style:1.0" "#,
+    r#"xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0" "#,
+    r#"xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0" "#,
+    r#"xmlns:number="urn:oasis:names:tc:opendocument:xmlns:datastyle:1.0" "#,
+    r#"xmlns:fo="urn:oasis:names:tc:opendocument:xmlns:xsl-fo-compatible:1.0" "#,
+    r#"xmlns:of="urn:oasis:names:tc:opendocument:xmlns:of:1.2""#,
+);
+
+/// Formatos de número y estilos de celda: montos con separadores argentinos
+/// ("$ 1.234.567,89", "U$S 1.500,00") y fechas dd/mm/aaaa
+const ESTILOS: &str = concat!(
+    r#"<number:currency-style style:name="N_ARS" number:language="es" number:country="AR">"#,
+    r#"<number:currency-symbol>$</number:currency-symbol><number:text> </number:text>"#,
+    r#"<number:number number:decimal-places="2" number:min-decimal-places="2" number:min-integer-digits="1" number:grouping="true"/>"#,
+    r#"</number:currency-style>"#,
+    r#"<number:currency-style style:name="N_USD" number:language="es" number:country="AR">"#,
+    r#"<number:currency-symbol>U$S</number:currency-symbol><number:text> </number:text>"#,
+    r#"<number:number number:decimal-places="2" number:min-decimal-places="2" number:min-integer-digits="1" number:grouping="true"/>"#,
+    r#"</number:currency-style>"#,
+    r#"<number:date-style style:name="N_FECHA">"#,
+    r#"<number:day number:style="long"/><number:text>/</number:text>"#,
+    r#"<number:month number:style="long"/><number:text>/</number:text>"#,
+    r#"<number:year number:style="long"/>"#,
+    r#"</number:date-style>"#,
+    r#"<style:style style:name="ars" style:family="table-cell" style:data-style-name="N_ARS"/>"#,
+    r#"<style:style style:name="usd" style:family="table-cell" style:data-style-name="N_USD"/>"#,
+    r#"<style:style style:name="fecha" style:family="table-cell" style:data-style-name="N_FECHA"/>"#,
+);
+
+/// Valor de una celda de la planilla.
+enum Celda {
+    Vacia,
+    Texto(String),
+    Numero(f64),
+    /// Monto con el formato de su moneda ("ARS" o "USD")
+    Monto(f64, &'static str),
+    Fecha(NaiveDate),
+    /// Fórmula de ODF y su resultado
+    Formula(String, Box<Celda>),
+}
+
+/// Hoja de la planilla: nombre, filas y cantidad de columnas.
+struct Hoja {
+    nombre: &'static str,
+    filas: Vec<Vec<Celda>>,
+    columnas: usize,
+}
+
+/// Guarda los datos en una planilla ODS con las hojas REND y PDF.
+///
+/// Igual que con el archivo Excel, los datos de rendición de un archivo
+/// existente se conservan y con `opciones.anexar` se agregan los registros a
+/// los de la hoja PDF. Sin extensión, se agrega `.ods`. Devuelve los mensajes
+/// para el usuario, como en [`guardar_y_formatear_excel`](crate::guardar_y_formatear_excel).
+pub fn guardar_ods(
+    datos: &[DatosPagina],
+    path: &Path,
+    opciones: &OpcionesExcel,
+) -> Result<Vec<String>, ProcError> {
+    let path = &if path.extension().is_none() {
+        path.with_extension("ods")
+    } else {
+        path.to_path_buf()
+    };
+
+    let existentes = if path.exists() {
+        excel::leer_existente_ods(path, opciones).map_err(|source| ProcError::OdsRead {
+            path: path.to_path_buf(),
+            source,
+        })?
+    } else {
+        Default::default()
+    };
+
+    let planilla = excel::armar_planilla(existentes, datos, opciones);
+    escribir_ods(&planilla, path, opciones).map_err(|source| ProcError::Exportar {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    Ok(planilla.mensajes)
+}
+
+/// Arma las hojas y escribe el archivo.
+fn escribir_ods(planilla: &Planilla, path: &Path, opciones: &OpcionesExcel) -> io::Result<()> {
+    let disposicion = Disposicion::desde_opciones(opciones);
+    let mut hojas = Vec::new();
+    if !opciones.sin_rend {
+        hojas.push(hoja_rend(planilla, &disposicion));
+    }
+    hojas.push(hoja_pdf(planilla, &disposicion, opciones));
+
+    let mut zip = ZipWriter::new(File::create(path)?);
+    // El tipo de archivo va primero y sin comprimir, para que se reconozca
+    let sin_comprimir = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    zip.start_file("mimetype", sin_comprimir)?;
+    zip.write_all(MIME_ODS.as_bytes())?;
+
+    let comprimido = SimpleFileOptions::default();
+    zip.start_file("META-INF/manifest.xml", comprimido)?;
+    zip.write_all(manifiesto().as_bytes())?;
+    zip.start_file("meta.xml", comprimido)?;
+    zip.write_all(metadatos(&planilla.datos, opciones).as_bytes())?;
+    zip.start_file("content.xml", comprimido)?;
+    zip.write_all(contenido(&hojas).as_bytes())?;
+    zip.finish()?;
+    Ok(())
+}
+
+/// Rango absoluto de una columna de otra hoja, de la fila `primera` a `ultima`:
+/// `[$PDF.$D$2:.$D$9]`.
+fn rango(hoja: &str, columna: &str, primera: usize, ultima: usize) -> String {
+    format!(
+        "[${hoja}.${columna}${}:.${columna}${}]",
+        primera,
+        ultima.max(primera),
+        hoja = hoja,
+        columna = columna
+    )
+}
+
+/// Monto de la hoja PDF como número, si lo es.
+fn monto_pdf(dato: &DatosPagina) -> Option<f64> {
+    dato.monto.parse::<f64>().ok()
+}
+
+/// Hoja REND con los datos de rendición, las fórmulas de cruce con la hoja PDF
+/// y la fila de totales.
+fn hoja_rend(planilla: &Planilla, disposicion: &Disposicion) -> Hoja {
+    let datos = &planilla.datos;
+    // Cheques repetidos en la hoja PDF, para marcarlos en REND
+    let duplicados: HashSet<String> = detectar_duplicados(datos)
+        .into_iter()
+        .map(|d| d.cheque)
+        .collect();
+
+    // Columnas de la hoja PDF que consultan las fórmulas
+    let primera_pdf = disposicion.numero_fila(0) as usize;
+    let ultima_pdf = primera_pdf + datos.len().saturating_sub(1);
+    let columna_pdf = |columna| rango("PDF", &disposicion.letra(columna), primera_pdf, ultima_pdf);
+    let exp = columna_pdf(ColumnaPdf::Expediente);
+    let a = columna_pdf(ColumnaPdf::Año);
+    let m = columna_pdf(ColumnaPdf::Monto);
+    let ch = columna_pdf(ColumnaPdf::Cheque);
+    // Celda de una columna de la hoja PDF, para las filas cruzadas por aproximación
+    let celda_pdf =
+        |columna: ColumnaPdf, fila: u32| format!("[$PDF.${}${}]", disposicion.letra(columna), fila);
+
+    let columnas = planilla.encabezados_rend.len();
+    let mut filas: Vec<Vec<Celda>> = vec![planilla
+        .encabezados_rend
+        .iter()
+        .map(|h| Celda::Texto(h.clone()))
+        .collect()];
+
+    for (i, fila) in planilla.filas_rend.iter().enumerate() {
+        let row = i + 2;
+        let monto = fila.get(1).and_then(|m| monto_rend(m));
+        let mut celdas: Vec<Celda> = fila
+            .iter()
+            .enumerate()
+            .map(|(col, valor)| match (col, monto) {
+                (1, Some(monto)) => Celda::Monto(monto, MONEDA_PESOS),
+                _ => Celda::Texto(valor.clone()),
+            })
+            .collect();
+        celdas.resize_with(columnas, || Celda::Vacia);
+
+        // Columna F (índice 5) es Observaciones - avisar cheques duplicados y cruces
+        let cheque = fila.first().map(String::as_str).unwrap_or("");
+        let cruce = planilla.cruces.get(i).copied().flatten();
+        let fila_cruce = cruce.map(|indice| disposicion.numero_fila(indice) as usize);
+        if let Some(observacion) =
+            excel::observaciones_rend(fila, duplicados.contains(cheque), fila_cruce)
+        {
+            celdas[5] = Celda::Texto(observacion);
+        }
+
+        let resultados = excel::resultados_rend(datos, fila, cruce);
+        let año = Box::new(
+            resultados
+                .año
+                .map_or(Celda::Texto(String::new()), Celda::Numero),
+        );
+
+        // Fila cruzada por aproximación: las fórmulas apuntan a su registro
+        let (formula_d, formula_e, formula_h) = if let Some(indice) = cruce {
+            let fila_pdf = disposicion.numero_fila(indice);
+            let año_pdf = celda_pdf(ColumnaPdf::Año, fila_pdf);
+            (
+                format!("of:={}", celda_pdf(ColumnaPdf::Expediente, fila_pdf)),
+                format!("of:=IF({0}>0;{0};\"\")", año_pdf),
+                format!(
+                    "of:=COUNTIF({};{})",
+                    ch,
+                    celda_pdf(ColumnaPdf::Cheque, fila_pdf)
+                ),
+            )
+        } else {
+            (
+                format!(
+                    "of:=IFERROR(INDEX({exp};MATCH([.A{0}];{ch};0));INDEX({exp};MATCH([.B{0}];{m};0)))",
+                    row,
+                    exp = exp,
+                    ch = ch,
+                    m = m
+                ),
+                format!(
+                    "of:=IFERROR(IF(INDEX({a};MATCH([.A{0}];{ch};0))>0;INDEX({a};MATCH([.A{0}];{ch};0));\"\");IF(INDEX({a};MATCH([.B{0}];{m};0))>0;INDEX({a};MATCH([.B{0}];{m};0));\"\"))",
+                    row,
+                    a = a,
+                    ch = ch,
+                    m = m
+                ),
+                format!("of:=COUNTIF({};[.A{}])", ch, row),
+            )
+        };
+        // Sin registro en la hoja PDF se conserva el expediente que tenía la fila
+        let formula_d = match &resultados.expediente_previo {
+            Some(previo) => format!(
+                "of:=IFERROR({};\"{}\")",
+                &formula_d["of:=".len()..],
+                previo.replace('"', "\"\"")
+            ),
+            None => formula_d,
+        };
+
+        // D: Expediente, E: Año, G: Control, H: Control cheque
+        celdas[3] = Celda::Formula(formula_d, Box::new(Celda::Texto(resultados.expediente)));
+        celdas[4] = Celda::Formula(formula_e, año);
+        celdas[6] = Celda::Formula(
+            format!("of:=COUNTIF({};[.B{}])", m, row),
+            Box::new(Celda::Numero(resultados.por_monto as f64)),
+        );
+        celdas[7] = Celda::Formula(
+            formula_h,
+            Box::new(Celda::Numero(resultados.por_cheque as f64)),
+        );
+
+        filas.push(celdas);
+    }
+
+    // Fila de totales: suma del Monto de las filas visibles (SUBTOTAL 109)
+    if !planilla.filas_rend.is_empty() {
+        let total: f64 = planilla
+            .filas_rend
+            .iter()
+            .filter_map(|fila| fila.get(1).and_then(|m| monto_rend(m)))
+            .sum();
+        filas.push(vec![
+            Celda::Texto(ETIQUETA_TOTAL.to_string()),
+            Celda::Formula(
+                format!(
+                    "of:=SUBTOTAL(109;[.B2:.B{}])",
+                    planilla.filas_rend.len() + 1
+                ),
+                Box::new(Celda::Monto(total, MONEDA_PESOS)),
+            ),
+        ]);
+    }
+
+    Hoja {
+        nombre: HOJA_REND,
+        filas,
+        columnas,
+    }
+}
+
+/// Hoja PDF con los registros extraídos y, si hay hoja REND, las fórmulas de control.
+fn hoja_pdf(planilla: &Planilla, disposicion: &Disposicion, opciones: &OpcionesExcel) -> Hoja {
+    let datos = &planilla.datos;
+    // Los campos de los extractores propios van al final
+    let extra = nombres_campos_extra(datos);
+    let mut encabezados = disposicion.ordenar(&opciones.idioma.columnas_pdf());
+    encabezados.extend(&extra);
+    let columnas = encabezados.len();
+
+    let ultima_rend = planilla.filas_rend.len() + 1;
+    let montos_rend: Vec<Option<f64>> = planilla
+        .filas_rend
+        .iter()
+        .map(|fila| fila.get(1).and_then(|m| monto_rend(m)))
+        .collect();
+    let cheques_rend: Vec<String> = planilla
+        .filas_rend
+        .iter()
+        .map(|fila| fila.first().map(|c| c.to_lowercase()).unwrap_or_default())
+        .collect();
+    // Fila de REND con la que se cruzó cada registro por aproximación
+    let mut cruzados = HashMap::new();
+    for (fila, cruce) in planilla.cruces.iter().enumerate() {
+        if let Some(i) = cruce {
+            cruzados.entry(*i).or_insert(fila);
+        }
+    }
+
+    // Título o membrete de la plantilla, sobre los encabezados
+    let mut filas: Vec<Vec<Celda>> = Vec::new();
+    filas.resize_with(disposicion.fila_encabezados() as usize, Vec::new);
+    for (fila, col, texto) in &planilla.membrete_pdf {
+        let fila = &mut filas[*fila as usize];
+        if fila.len() <= *col as usize {
+            fila.resize_with(*col as usize + 1, || Celda::Vacia);
+        }
+        fila[*col as usize] = Celda::Texto(texto.clone());
+    }
+    let columnas = filas.iter().map(Vec::len).fold(columnas, usize::max);
+
+    filas.push(
+        encabezados
+            .iter()
+            .map(|h| Celda::Texto(h.to_string()))
+            .collect(),
+    );
+
+    for (i, dato) in datos.iter().enumerate() {
+        let row = disposicion.numero_fila(i);
+        let mut celdas: Vec<Celda> = Vec::new();
+        celdas.resize_with(columnas, || Celda::Vacia);
+        let mut poner = |columna: ColumnaPdf, celda: Celda| {
+            celdas[disposicion.col(columna) as usize] = celda;
+        };
+
+        poner(ColumnaPdf::Nombre, Celda::Texto(dato.nombre.clone()));
+        poner(
+            ColumnaPdf::Expediente,
+            Celda::Texto(dato.expediente.clone()),
+        );
+
+        // Año y monto como números si es posible
+        poner(
+            ColumnaPdf::Año,
+            match dato.año.trim().parse::<f64>() {
+                Ok(año) => Celda::Numero(año),
+                Err(_) => Celda::Texto(dato.año.clone()),
+            },
+        );
+        let moneda = if dato.moneda == MONEDA_DOLARES {
+            MONEDA_DOLARES
+        } else {
+            MONEDA_PESOS
+        };
+        let monto = monto_pdf(dato);
+        poner(
+            ColumnaPdf::Monto,
+            match monto {
+                Some(monto) => Celda::Monto(monto, moneda),
+                None => Celda::Texto(dato.monto.clone()),
+            },
+        );
+        poner(
+            ColumnaPdf::MontoOriginal,
+            Celda::Texto(dato.monto_original.clone()),
+        );
+        poner(ColumnaPdf::Cheque, Celda::Texto(dato.cheque.clone()));
+
+        // Fórmulas de control; sin hoja REND las columnas quedan vacías
+        if !opciones.sin_rend {
+            let por_monto = montos_rend
+                .iter()
+                .filter(|m| monto.is_some() && **m == monto)
+                .count();
+            poner(
+                ColumnaPdf::Control,
+                Celda::Formula(
+                    format!(
+                        "of:=COUNTIF({};[.{}{}])",
+                        rango(HOJA_REND, "B", 2, ultima_rend),
+                        disposicion.letra(ColumnaPdf::Monto),
+                        row
+                    ),
+                    Box::new(Celda::Numero(por_monto as f64)),
+                ),
+            );
+            // Un registro cruzado por aproximación se cuenta con el cheque de su fila de REND
+            let (cheque, referencia) = match cruzados.get(&i) {
+                Some(&fila) => (
+                    cheques_rend[fila].clone(),
+                    format!("[$REND.$A${}]", fila + 2),
+                ),
+                None => (
+                    dato.cheque.to_lowercase(),
+                    format!("[.{}{}]", disposicion.letra(ColumnaPdf::Cheque), row),
+                ),
+            };
+            let por_cheque = cheques_rend.iter().filter(|c| **c == cheque).count();
+            poner(
+                ColumnaPdf::ControlCheque,
+                Celda::Formula(
+                    format!(
+                        "of:=COUNTIF({};{})",
+                        rango(HOJA_REND, "A", 2, ultima_rend),
+                        referencia
+                    ),
+                    Box::new(Celda::Numero(por_cheque as f64)),
+                ),
+            );
+        }
+
+        poner(ColumnaPdf::Archivo, Celda::Texto(dato.archivo.clone()));
+        poner(
+            ColumnaPdf::Observaciones,
+            Celda::Texto(dato.observaciones.join("; ")),
+        );
+        poner(ColumnaPdf::Moneda, Celda::Texto(dato.moneda.clone()));
+        poner(ColumnaPdf::Juzgado, Celda::Texto(dato.juzgado.clone()));
+        if let Some(fecha) = dato.fecha {
+            poner(ColumnaPdf::Fecha, Celda::Fecha(fecha));
+        }
+        poner(ColumnaPdf::Pagina, Celda::Numero(dato.pagina as f64));
+
+        for (i, nombre) in extra.iter().enumerate() {
+            celdas[ColumnaPdf::TODAS.len() + i] =
+                Celda::Texto(dato.campo_extra(nombre).to_string());
+        }
+        filas.push(celdas);
+    }
+
+    Hoja {
+        nombre: "PDF",
+        filas,
+        columnas,
+    }
+}
+
+/// Escapa el texto para usarlo en el XML, descartando los caracteres de control
+/// que XML no admite.
+fn escapar(texto: &str) -> String {
+    let mut escapado = String::with_capacity(texto.len());
+    for c in texto.chars() {
+        match c {
+            '&' => escapado.push_str("&amp;"),
+            '<' => escapado.push_str("&lt;"),
+            '>' => escapado.push_str("&gt;"),
+            '"' => escapado.push_str("&quot;"),
+            '\'' => escapado.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escapado.push(c),
+            c if c.is_control() => {}
+            c => escapado.push(c),
+        }
+    }
+    escapado
+}
+
+/// Agrega la celda al XML de la hoja.
+fn escribir_celda(xml: &mut String, celda: &Celda) {
+    let (formula, valor) = match celda {
+        Celda::Formula(formula, valor) => (Some(formula), valor.as_ref()),
+        otra => (None, otra),
+    };
+    xml.push_str("<table:table-cell");
+    if let Some(formula) = formula {
+        let _ = write!(xml, r#" table:formula="{}""#, escapar(formula));
+    }
+    let visible = match valor {
+        Celda::Vacia | Celda::Formula(..) => {
+            xml.push_str("/>");
+            return;
+        }
+        Celda::Texto(texto) => {
+            xml.push_str(r#" office:value-type="string""#);
+            texto.clone()
+        }
+        Celda::Numero(numero) => {
+            let _ = write!(
+                xml,
+                r#" office:value-type="float" office:value="{}""#,
+                numero
+            );
+            numero.to_string()
+        }
+        Celda::Monto(monto, moneda) => {
+            let estilo = if *moneda == MONEDA_DOLARES {
+                "usd"
+            } else {
+                "ars"
+            };
+            let _ = write!(
+                xml,
+                r#" table:style-name="{}" office:value-type="currency" office:currency="{}" office:value="{}""#,
+                estilo, moneda, monto
+            );
+            excel::monto_visible(&monto.to_string(), moneda)
+        }
+        Celda::Fecha(fecha) => {
+            let _ = write!(
+                xml,
+                r#" table:style-name="fecha" office:value-type="date" office:date-value="{}""#,
+                fecha.format("%Y-%m-%d")
+            );
+            fecha.format("%d/%m/%Y").to_string()
+        }
+    };
+    let _ = write!(
+        xml,
+        "><text:p>{}</text:p></table:table-cell>",
+        escapar(&visible)
+    );
+}
+
+/// `content.xml`: estilos y hojas.
+fn contenido(hojas: &[Hoja]) -> String {
+    let mut xml = String::new();
+    let _ = write!(
+        xml,
+        r#"<?xml version="1.0" encoding="UTF-8"?><office:document-content {} office:version="1.2"><office:automatic-styles>{}</office:automatic-styles><office:body><office:spreadsheet>"#,
+        NAMESPACES, ESTILOS
+    );
+    for hoja in hojas {
+        let _ = write!(
+            xml,
+            r#"<table:table table:name="{}"><table:table-column table:number-columns-repeated="{}"/>"#,
+            escapar(hoja.nombre),
+            hoja.columnas.max(1)
+        );
+        for fila in &hoja.filas {
+            xml.push_str("<table:table-row>");
+            if fila.is_empty() {
+                xml.push_str("<table:table-cell/>");
+            }
+            for celda in fila {
+                escribir_celda(&mut xml, celda);
+            }
+            xml.push_str("</table:table-row>");
+        }
+        xml.push_str("</table:table>");
+    }
+    xml.push_str("</office:spreadsheet></office:body></office:document-content>");
+    xml
+}
+
+/// `META-INF/manifest.xml`: los archivos que forman el documento.
+fn manifiesto() -> String {
+    format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+            r#"<manifest:manifest xmlns:manifest="urn:oasis:names:tc:opendocument:xmlns:manifest:1.0" manifest:version="1.2">"#,
+            r#"<manifest:file-entry manifest:full-path="/" manifest:version="1.2" manifest:media-type="{}"/>"#,
+            r#"<manifest:file-entry manifest:full-path="content.xml" manifest:media-type="text/xml"/>"#,
+            r#"<manifest:file-entry manifest:full-path="meta.xml" manifest:media-type="text/xml"/>"#,
+            r#"</manifest:manifest>"#
+        ),
+        MIME_ODS
+    )
+}
+
+/// `meta.xml`: las mismas propiedades del documento que el archivo Excel.
+fn metadatos(datos: &[DatosPagina], opciones: &OpcionesExcel) -> String {
+    let ahora = chrono::Utc::now();
+    let version = crate::version();
+    let (origen, archivos) = excel::origen(datos, opciones);
+    let comentario = format!(
+        "Generado por pdf-procuracion {} el {} a partir de {} ({} registros)",
+        version,
+        ahora
+            .with_timezone(&chrono::Local)
+            .format("%d/%m/%Y %H:%M:%S"),
+        origen,
+        datos.len()
+    );
+    let propia = |nombre: &str, valor: &str| {
+        format!(
+            r#"<meta:user-defined meta:name="{}">{}</meta:user-defined>"#,
+            escapar(nombre),
+            escapar(valor)
+        )
+    };
+    format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+            r#"<office:document-meta xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" "#,
+            r#"xmlns:meta="urn:oasis:names:tc:opendocument:xmlns:meta:1.0" "#,
+            r#"xmlns:dc="http://purl.org/dc/elements/1.1/" office:version="1.2"><office:meta>"#,
+            r#"<meta:generator>pdf-procuracion/{}</meta:generator>"#,
+            r#"<dc:title>{}</dc:title><dc:description>{}</dc:description>"#,
+            r#"<meta:creation-date>{}</meta:creation-date>{}{}{}{}"#,
+            r#"</office:meta></office:document-meta>"#
+        ),
+        escapar(&version),
+        escapar(&format!("Procuración - {}", origen)),
+        escapar(&comentario),
+        ahora.format("%Y-%m-%dT%H:%M:%S"),
+        propia("Origen", &origen),
+        propia("Archivos", &archivos),
+        propia("Versión", &version),
+        propia("Registros", &datos.len().to_string()),
+    )
+}
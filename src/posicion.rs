@@ -0,0 +1,245 @@
+//! Extracción del texto de una página respetando la posición de cada fragmento.
+//!
+//! `Document::extract_text` devuelve el texto en el orden en que aparece en el
+//! flujo de contenido; en los documentos a dos columnas eso intercala las
+//! columnas y los patrones terminan coincidiendo entre una y otra. Aquí se
+//! recorren los operadores de texto, se calcula la posición de cada fragmento y
+//! la página se lee columna por columna, de arriba hacia abajo.
+
+use lopdf::content::Content;
+use lopdf::{Document, Encoding, Object, ObjectId};
+use std::collections::BTreeMap;
+
+/// Ancho de página cuando el PDF no declara `MediaBox` (carta, en puntos)
+const ANCHO_PREDETERMINADO: f32 = 612.0;
+
+/// Diferencia vertical máxima, en puntos, entre fragmentos de la misma línea
+const TOLERANCIA_LINEA: f32 = 2.0;
+
+/// Fragmento de texto ubicado en la página.
+struct Fragmento {
+    x: f32,
+    y: f32,
+    texto: String,
+}
+
+/// Matriz de transformación [a b c d e f] de PDF.
+type Matriz = [f32; 6];
+
+const IDENTIDAD: Matriz = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+/// Producto `m1 × m2`, que aplica primero `m1` y después `m2`.
+fn multiplicar(m1: &Matriz, m2: &Matriz) -> Matriz {
+    [
+        m1[0] * m2[0] + m1[1] * m2[2],
+        m1[0] * m2[1] + m1[1] * m2[3],
+        m1[2] * m2[0] + m1[3] * m2[2],
+        m1[2] * m2[1] + m1[3] * m2[3],
+        m1[4] * m2[0] + m1[5] * m2[2] + m2[4],
+        m1[4] * m2[1] + m1[5] * m2[3] + m2[5],
+    ]
+}
+
+fn traslacion(tx: f32, ty: f32) -> Matriz {
+    [1.0, 0.0, 0.0, 1.0, tx, ty]
+}
+
+/// Operandos numéricos de un operador; los que no son números valen cero.
+fn numeros(operandos: &[Object]) -> Vec<f32> {
+    operandos
+        .iter()
+        .map(|o| o.as_float().unwrap_or(0.0))
+        .collect()
+}
+
+/// Límites horizontales de la página según su `MediaBox`, propio o heredado.
+fn limites_horizontales(doc: &Document, page_id: ObjectId) -> (f32, f32) {
+    let mut nodo = doc.get_dictionary(page_id).ok();
+    while let Some(diccionario) = nodo {
+        if let Ok(caja) = diccionario.get(b"MediaBox").and_then(Object::as_array) {
+            let caja = numeros(caja);
+            if caja.len() == 4 && caja[2] > caja[0] {
+                return (caja[0], caja[2]);
+            }
+        }
+        nodo = diccionario
+            .get(b"Parent")
+            .and_then(Object::as_reference)
+            .and_then(|id| doc.get_dictionary(id))
+            .ok();
+    }
+    (0.0, ANCHO_PREDETERMINADO)
+}
+
+/// Estado del texto mientras se recorre el flujo de contenido.
+struct Estado<'a> {
+    ctm: Matriz,
+    pila: Vec<Matriz>,
+    tm: Matriz,
+    tlm: Matriz,
+    interlineado: f32,
+    codificacion: Option<&'a Encoding<'a>>,
+    /// Si no hubo un operador de posición desde el último texto: el próximo
+    /// texto sigue al anterior en la misma línea
+    continua: bool,
+}
+
+impl Estado<'_> {
+    fn nueva_linea(&mut self, tx: f32, ty: f32) {
+        self.tlm = multiplicar(&traslacion(tx, ty), &self.tlm);
+        self.tm = self.tlm;
+        self.continua = false;
+    }
+
+    /// Agrega el texto en la posición actual, o a continuación del anterior si
+    /// no se reposicionó el cursor (sin las métricas de la fuente no se sabe
+    /// cuánto avanzó).
+    fn mostrar(&mut self, bytes: &[u8], fragmentos: &mut Vec<Fragmento>) {
+        let Some(codificacion) = self.codificacion else {
+            return;
+        };
+        let Ok(texto) = Document::decode_text(codificacion, bytes) else {
+            return;
+        };
+        match fragmentos.last_mut() {
+            Some(ultimo) if self.continua => ultimo.texto.push_str(&texto),
+            _ => {
+                let posicion = multiplicar(&self.tm, &self.ctm);
+                fragmentos.push(Fragmento {
+                    x: posicion[4],
+                    y: posicion[5],
+                    texto,
+                });
+            }
+        }
+        self.continua = true;
+    }
+}
+
+/// Extrae el texto de la página `page_num` leyéndola en `columnas` columnas de
+/// igual ancho, de izquierda a derecha, y cada columna de arriba hacia abajo.
+pub(crate) fn extraer_texto_por_columnas(
+    doc: &Document,
+    page_num: u32,
+    columnas: usize,
+) -> lopdf::Result<String> {
+    let page_id = *doc
+        .get_pages()
+        .get(&page_num)
+        .ok_or(lopdf::Error::PageNumberNotFound(page_num))?;
+    let encodings: BTreeMap<Vec<u8>, Encoding> = doc
+        .get_page_fonts(page_id)?
+        .into_iter()
+        .map(|(nombre, fuente)| fuente.get_font_encoding(doc).map(|e| (nombre, e)))
+        .collect::<lopdf::Result<_>>()?;
+    let contenido = Content::decode(&doc.get_page_content(page_id)?)?;
+
+    let mut estado = Estado {
+        ctm: IDENTIDAD,
+        pila: Vec::new(),
+        tm: IDENTIDAD,
+        tlm: IDENTIDAD,
+        interlineado: 0.0,
+        codificacion: None,
+        continua: false,
+    };
+    let mut fragmentos = Vec::new();
+    for operacion in &contenido.operations {
+        let operandos = &operacion.operands;
+        let n = numeros(operandos);
+        match operacion.operator.as_str() {
+            "q" => estado.pila.push(estado.ctm),
+            "Q" => estado.ctm = estado.pila.pop().unwrap_or(IDENTIDAD),
+            "cm" if n.len() == 6 => {
+                let m = [n[0], n[1], n[2], n[3], n[4], n[5]];
+                estado.ctm = multiplicar(&m, &estado.ctm);
+            }
+            "BT" => {
+                estado.tm = IDENTIDAD;
+                estado.tlm = IDENTIDAD;
+                estado.continua = false;
+            }
+            "Tf" => {
+                estado.codificacion = operandos
+                    .first()
+                    .and_then(|o| o.as_name().ok())
+                    .and_then(|nombre| encodings.get(nombre));
+            }
+            "TL" => estado.interlineado = n.first().copied().unwrap_or(0.0),
+            "Tm" if n.len() == 6 => {
+                estado.tlm = [n[0], n[1], n[2], n[3], n[4], n[5]];
+                estado.tm = estado.tlm;
+                estado.continua = false;
+            }
+            "Td" if n.len() == 2 => estado.nueva_linea(n[0], n[1]),
+            "TD" if n.len() == 2 => {
+                estado.interlineado = -n[1];
+                estado.nueva_linea(n[0], n[1]);
+            }
+            "T*" => estado.nueva_linea(0.0, -estado.interlineado),
+            "Tj" | "'" | "\"" => {
+                if operacion.operator != "Tj" {
+                    estado.nueva_linea(0.0, -estado.interlineado);
+                }
+                if let Some(Object::String(bytes, _)) = operandos.last() {
+                    estado.mostrar(bytes, &mut fragmentos);
+                }
+            }
+            "TJ" => {
+                let elementos = operandos.first().and_then(|o| o.as_array().ok());
+                for elemento in elementos.into_iter().flatten() {
+                    match elemento {
+                        Object::String(bytes, _) => estado.mostrar(bytes, &mut fragmentos),
+                        // Un desplazamiento grande (en milésimas de la fuente)
+                        // separa palabras, como en `extract_text`
+                        otro if otro.as_float().unwrap_or(0.0) < -100.0 => {
+                            if let Some(ultimo) = fragmentos.last_mut().filter(|_| estado.continua)
+                            {
+                                ultimo.texto.push(' ');
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let (inicio, fin) = limites_horizontales(doc, page_id);
+    let ancho_columna = (fin - inicio) / columnas.max(1) as f32;
+    let columna = |x: f32| (((x - inicio) / ancho_columna).max(0.0) as usize).min(columnas - 1);
+
+    // Columna, luego de arriba hacia abajo, luego de izquierda a derecha
+    fragmentos.sort_by(|a, b| {
+        columna(a.x)
+            .cmp(&columna(b.x))
+            .then(b.y.total_cmp(&a.y))
+            .then(a.x.total_cmp(&b.x))
+    });
+
+    let mut texto = String::new();
+    let mut anterior: Option<(usize, f32)> = None;
+    let mut linea: Vec<&Fragmento> = Vec::new();
+    let volcar = |linea: &mut Vec<&Fragmento>, texto: &mut String| {
+        linea.sort_by(|a, b| a.x.total_cmp(&b.x));
+        let partes: Vec<&str> = linea.iter().map(|f| f.texto.as_str()).collect();
+        texto.push_str(&partes.join(" "));
+        texto.push('\n');
+        linea.clear();
+    };
+    for fragmento in &fragmentos {
+        let actual = (columna(fragmento.x), fragmento.y);
+        let misma_linea = anterior
+            .is_some_and(|(col, y)| col == actual.0 && (y - actual.1).abs() <= TOLERANCIA_LINEA);
+        if !misma_linea && !linea.is_empty() {
+            volcar(&mut linea, &mut texto);
+        }
+        linea.push(fragmento);
+        anterior = Some(actual);
+    }
+    if !linea.is_empty() {
+        volcar(&mut linea, &mut texto);
+    }
+    Ok(texto)
+}
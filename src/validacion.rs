@@ -0,0 +1,232 @@
+//! Controles que se aplican sobre el conjunto de registros ya extraídos.
+
+use crate::extraccion::normalizar_monto;
+use crate::{Confianza, DatosPagina, ProcError};
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+/// Cheque repetido y los índices de los registros donde aparece.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Duplicado {
+    pub cheque: String,
+    pub indices: Vec<usize>,
+}
+
+/// Busca números de cheque que aparecen en más de un registro.
+///
+/// Los duplicados rompen las fórmulas de control `COUNTIF` de ambas hojas.
+/// Los cheques no encontrados (vacíos o con el número de página de respaldo)
+/// no se comparan. Los registros de una misma página (una por carátula con
+/// `todas_las_caratulas`) comparten el cheque sin ser duplicados entre sí.
+pub fn detectar_duplicados(datos: &[DatosPagina]) -> Vec<Duplicado> {
+    let mut por_cheque: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (i, dato) in datos.iter().enumerate() {
+        if dato.cheque.is_empty() || dato.confianza.cheque == Confianza::Respaldo {
+            continue;
+        }
+        por_cheque.entry(dato.cheque.as_str()).or_default().push(i);
+    }
+
+    por_cheque
+        .into_iter()
+        .filter(|(_, indices)| {
+            let paginas: HashSet<(&str, usize)> = indices
+                .iter()
+                .map(|&i| (datos[i].archivo.as_str(), datos[i].pagina))
+                .collect();
+            paginas.len() > 1
+        })
+        .map(|(cheque, indices)| Duplicado {
+            cheque: cheque.to_string(),
+            indices,
+        })
+        .collect()
+}
+
+/// Suma de los montos extraídos, separada por moneda.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Totales {
+    /// Suma y cantidad de montos por moneda ("ARS", "USD")
+    pub por_moneda: BTreeMap<String, (f64, usize)>,
+    /// Registros en los que no se encontró monto
+    pub sin_monto: usize,
+    /// Registros con un monto que no se pudo interpretar como número
+    pub no_numericos: usize,
+}
+
+/// Suma los montos de los registros, por moneda.
+///
+/// Los registros sin monto (moneda vacía, el monto es el número de página) y
+/// los montos no numéricos se cuentan aparte para saber que la suma está incompleta.
+pub fn totalizar(datos: &[DatosPagina]) -> Totales {
+    let mut totales = Totales::default();
+    for dato in datos {
+        if dato.moneda.is_empty() {
+            totales.sin_monto += 1;
+            continue;
+        }
+        match dato.monto.parse::<f64>() {
+            Ok(monto) => {
+                let total = totales.por_moneda.entry(dato.moneda.clone()).or_default();
+                total.0 += monto;
+                total.1 += 1;
+            }
+            Err(_) => totales.no_numericos += 1,
+        }
+    }
+    totales
+}
+
+/// Une los registros partidos entre dos páginas consecutivas del mismo PDF.
+///
+/// Un registro sin cheque se une con el de la página siguiente si este no tiene
+/// monto y ambos tienen exactamente el mismo expediente (encontrado, no de
+/// respaldo). El registro unido toma el cheque de la segunda página y completa
+/// con ella los demás campos que faltaban. Devuelve la cantidad de uniones.
+pub fn unir_continuaciones(datos: &mut Vec<DatosPagina>) -> usize {
+    let mut unidos: Vec<DatosPagina> = Vec::with_capacity(datos.len());
+    let mut uniones = 0;
+    for siguiente in datos.drain(..) {
+        let Some(actual) = unidos.last_mut() else {
+            unidos.push(siguiente);
+            continue;
+        };
+        let es_continuacion = actual.archivo == siguiente.archivo
+            && siguiente.pagina == actual.pagina + 1
+            && actual.confianza.cheque == Confianza::Respaldo
+            && siguiente.confianza.monto == Confianza::Respaldo
+            && actual.confianza.expediente != Confianza::Respaldo
+            && siguiente.confianza.expediente != Confianza::Respaldo
+            && actual.expediente == siguiente.expediente;
+        if !es_continuacion {
+            unidos.push(siguiente);
+            continue;
+        }
+
+        actual.cheque = siguiente.cheque;
+        actual.confianza.cheque = siguiente.confianza.cheque;
+        if actual.confianza.nombre == Confianza::Respaldo {
+            actual.nombre = siguiente.nombre;
+            actual.confianza.nombre = siguiente.confianza.nombre;
+        }
+        if actual.confianza.año == Confianza::Respaldo {
+            actual.año = siguiente.año;
+            actual.confianza.año = siguiente.confianza.año;
+        }
+        if actual.juzgado.is_empty() {
+            actual.juzgado = siguiente.juzgado;
+        }
+        if actual.fecha.is_none() {
+            actual.fecha = siguiente.fecha;
+        }
+        actual.observaciones.extend(siguiente.observaciones);
+        actual
+            .observaciones
+            .push(format!("Unido con la página {}", siguiente.pagina));
+        actual.actualizar_avisos();
+        uniones += 1;
+    }
+    *datos = unidos;
+    uniones
+}
+
+/// Conserva solo los registros cuyo expediente coincide con `patron`.
+///
+/// Se aplica sobre el expediente ya extraído y limpio; los registros sin
+/// expediente (con el número de página de respaldo) no coinciden salvo que el
+/// patrón lo permita. Devuelve la cantidad de registros descartados.
+pub fn filtrar_por_expediente(datos: &mut Vec<DatosPagina>, patron: &Regex) -> usize {
+    let antes = datos.len();
+    datos.retain(|dato| patron.is_match(&dato.expediente));
+    antes - datos.len()
+}
+
+/// Diferencia máxima entre el monto extraído y el esperado para considerarlos iguales.
+pub const TOLERANCIA_MONTO: f64 = 0.01;
+
+/// Dígitos de un número de cheque, para comparar "CH 12345678" con "12345678".
+fn clave_cheque(cheque: &str) -> String {
+    cheque.chars().filter(char::is_ascii_digit).collect()
+}
+
+/// Carga la lista oficial de montos esperados por cheque.
+///
+/// El archivo es un CSV de dos columnas, cheque y monto, con o sin fila de
+/// encabezados; el monto puede escribirse con cualquier separador decimal
+/// ("1.500,00" o "1500.00"). Los cheques se comparan por sus dígitos.
+pub fn cargar_montos_esperados(path: &Path) -> Result<HashMap<String, f64>, ProcError> {
+    let error = |mensaje: String| ProcError::MontosEsperados {
+        path: path.to_path_buf(),
+        mensaje,
+    };
+    let mut lector = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_path(path)
+        .map_err(|e| error(e.to_string()))?;
+
+    let mut esperados = HashMap::new();
+    for (i, fila) in lector.records().enumerate() {
+        let fila = fila.map_err(|e| error(e.to_string()))?;
+        let cheque = clave_cheque(fila.get(0).unwrap_or_default());
+        let monto = normalizar_monto(fila.get(1).unwrap_or_default()).parse::<f64>();
+        match monto {
+            Ok(monto) if !cheque.is_empty() => {
+                esperados.insert(cheque, monto);
+            }
+            // La primera fila puede ser de encabezados
+            _ if i == 0 => {}
+            _ => return Err(error(format!("fila {} inválida", i + 1))),
+        }
+    }
+    Ok(esperados)
+}
+
+/// Compara el monto de cada registro con el esperado para su cheque y anota en
+/// las observaciones los que difieren en más de `tolerancia`.
+///
+/// Los registros cuyo cheque no está en la lista no se controlan. Devuelve la
+/// cantidad de registros con diferencias.
+pub fn cotejar_montos(
+    datos: &mut [DatosPagina],
+    esperados: &HashMap<String, f64>,
+    tolerancia: f64,
+) -> usize {
+    let mut diferencias = 0;
+    for dato in datos.iter_mut() {
+        let Some(&esperado) = esperados.get(&clave_cheque(&dato.cheque)) else {
+            continue;
+        };
+        let coincide = !dato.moneda.is_empty()
+            && dato
+                .monto
+                .parse::<f64>()
+                .is_ok_and(|monto| (monto - esperado).abs() <= tolerancia);
+        if !coincide {
+            let detalle = format!("se esperaba {:.2}", esperado);
+            dato.observaciones
+                .push(crate::aviso("monto distinto del esperado", &detalle));
+            diferencias += 1;
+        }
+    }
+    diferencias
+}
+
+/// Marca en las observaciones los registros con cheque duplicado.
+///
+/// Devuelve los duplicados encontrados, para informarlos.
+pub fn marcar_duplicados(datos: &mut [DatosPagina]) -> Vec<Duplicado> {
+    let duplicados = detectar_duplicados(datos);
+
+    for duplicado in &duplicados {
+        for &i in &duplicado.indices {
+            datos[i]
+                .observaciones
+                .push(crate::aviso("cheque duplicado", ""));
+        }
+    }
+
+    duplicados
+}